@@ -4,15 +4,14 @@ use bevy::{
     render::camera::RenderTarget,
     sprite::MaterialMesh2dBundle,
     time::FixedTimestep,
+    utils::HashSet,
     window::{CreateWindow, WindowFocused, WindowId, WindowMode},
 };
+use bevy_glfw::{GlfwWindowStates, WindowMinimized};
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, StageLabel)]
 struct FixedUpdateStage;
 
-#[derive(Debug, Hash, PartialEq, Eq, Clone, StageLabel)]
-struct FocusedWindowDependant;
-
 fn main() {
     App::new()
         .insert_resource(WindowDescriptor {
@@ -284,12 +283,12 @@ fn toggle_maximized(
     input: Res<Input<KeyCode>>,
     focused: ResMut<LastFocusedWindow>,
     mut windows: ResMut<Windows>,
-    mut maximized: Local<bool>,
+    window_states: GlfwWindowStates,
 ) {
     if input.just_pressed(KeyCode::I) {
         let window = windows.get_mut(focused.0).unwrap();
-        *maximized = !*maximized;
-        window.set_maximized(dbg!(*maximized));
+        let maximized = window_states.is_maximized(focused.0);
+        window.set_maximized(dbg!(!maximized));
     }
 }
 
@@ -297,12 +296,20 @@ fn toggle_minimized(
     input: Res<Input<KeyCode>>,
     focused: ResMut<LastFocusedWindow>,
     mut windows: ResMut<Windows>,
-    mut minimized: Local<bool>,
+    mut minimized_events: EventReader<WindowMinimized>,
+    mut minimized: Local<HashSet<WindowId>>,
 ) {
+    for event in minimized_events.iter() {
+        if event.minimized {
+            minimized.insert(event.id);
+        } else {
+            minimized.remove(&event.id);
+        }
+    }
+
     if input.just_pressed(KeyCode::J) {
         let window = windows.get_mut(focused.0).unwrap();
-        *minimized = !*minimized;
-        window.set_minimized(dbg!(*minimized));
+        window.set_minimized(dbg!(!minimized.contains(&focused.0)));
     }
 }
 
@@ -0,0 +1,280 @@
+use crate::{
+    glfw_errors, glfw_frame_limiter::FrameLimiter, glfw_joysticks, glfw_windows::GlfwWindows,
+    handle_create_window_events, GlfwEventLoopProxy, GlfwSettings, GlfwUpdateMode,
+};
+use bevy::{
+    app::AppExit,
+    ecs::event::ManualEventReader,
+    prelude::*,
+    window::{CreateWindow, RequestRedraw},
+};
+use glfw_bindgen::*;
+use std::{cell::Cell, mem, ptr, time::Instant};
+
+thread_local! {
+    // runner waiting for events that should update on window refresh
+    static REFRESH_RUNNER: Cell<*mut GlfwAppRunner> = const { Cell::new(ptr::null_mut()) };
+}
+
+/// Drives an app frame by frame, for embedding it into an external loop.
+///
+/// [`glfw_run_return`](crate::glfw_run_return) is the equivalent of:
+/// ```ignore
+/// let mut runner = GlfwAppRunner::new(app);
+/// while !runner.should_exit() {
+///     runner.poll_events();
+///     runner.update();
+/// }
+/// ```
+pub struct GlfwAppRunner {
+    app: App,
+    app_exit_event_reader: ManualEventReader<AppExit>,
+    redraw_event_reader: ManualEventReader<RequestRedraw>,
+    redraw_requested: bool,
+    last_update: Instant,
+    proxy: GlfwEventLoopProxy,
+    frame_limiter: FrameLimiter,
+    should_exit: bool,
+    // sent during refresh callback updates, created in the next regular update
+    pending_create_window_events: Vec<CreateWindow>,
+}
+
+impl GlfwAppRunner {
+    /// `app` must have [`GlfwPlugin`](crate::GlfwPlugin) added.
+    pub fn new(app: App) -> Self {
+        let proxy = app.world.resource::<GlfwEventLoopProxy>().clone();
+        GlfwAppRunner {
+            app,
+            app_exit_event_reader: default(),
+            redraw_event_reader: default(),
+            redraw_requested: false,
+            last_update: Instant::now(),
+            proxy,
+            frame_limiter: FrameLimiter::new(),
+            should_exit: false,
+            pending_create_window_events: Vec::new(),
+        }
+    }
+
+    /// Waits for events according to the current [`GlfwUpdateMode`] and sends them into the world.
+    ///
+    /// May update the app from within, see [`GlfwSettings::update_on_refresh`].
+    pub fn poll_events(&mut self) {
+        let update_on_refresh = self.app.world.resource::<GlfwSettings>().update_on_refresh;
+        let runner: *mut Self = self;
+        unsafe {
+            if update_on_refresh {
+                REFRESH_RUNNER.with(|refresh_runner| refresh_runner.set(runner));
+            }
+
+            wait_for_events(runner);
+            REFRESH_RUNNER.with(|refresh_runner| refresh_runner.set(ptr::null_mut()));
+        }
+
+        self.send_events();
+    }
+
+    /// Updates the app once, then waits for the frame rate limit.
+    ///
+    /// Does nothing once [`should_exit`](Self::should_exit) returns `true`.
+    pub fn update(&mut self) {
+        self.update_app();
+        if self.should_exit {
+            return;
+        }
+
+        let world = &mut self.app.world;
+        unsafe {
+            world
+                .non_send_resource_mut::<GlfwWindows>()
+                .destroy_released_windows();
+        }
+
+        let pending_create_window_events = mem::take(&mut self.pending_create_window_events);
+        handle_create_window_events(world, pending_create_window_events);
+        self.finish_frame();
+    }
+
+    /// Whether an [`AppExit`] event was sent.
+    pub fn should_exit(&self) -> bool {
+        self.should_exit
+    }
+
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    pub fn into_app(mut self) -> App {
+        self.release_surfaces();
+        mem::replace(&mut self.app, App::empty())
+    }
+
+    fn send_events(&mut self) {
+        {
+            let world = self.app.world.cell();
+            let mut glfw_windows = world.non_send_resource_mut::<GlfwWindows>();
+            for (window_id, window) in glfw_windows.windows.iter_mut() {
+                let mut windows = world.resource_mut::<Windows>();
+                let bevy_window = windows.get_mut(*window_id).unwrap();
+                unsafe { window.handle_events(&world, bevy_window) };
+            }
+        }
+
+        glfw_errors::send_error_events(&mut self.app.world);
+        glfw_joysticks::send_joystick_events(&mut self.app.world);
+        self.proxy.send_user_events(&mut self.app.world);
+    }
+
+    fn update_app(&mut self) {
+        if self.should_exit {
+            return;
+        }
+
+        self.last_update = Instant::now();
+        self.app.update();
+
+        let world = &self.app.world;
+        if let Some(redraw_events) = world.get_resource::<Events<RequestRedraw>>() {
+            self.redraw_requested = self
+                .redraw_event_reader
+                .iter(redraw_events)
+                .last()
+                .is_some();
+        }
+
+        if let Some(app_exit_events) = world.get_resource::<Events<AppExit>>() {
+            if self
+                .app_exit_event_reader
+                .iter(app_exit_events)
+                .last()
+                .is_some()
+            {
+                self.should_exit = true;
+            }
+        }
+    }
+
+    fn finish_frame(&mut self) {
+        let world = &mut self.app.world;
+        let limit = frame_rate_limit(world);
+        self.frame_limiter.finish_frame(world, limit);
+    }
+
+    // the main world, and with it the windows, is dropped before the render world
+    fn release_surfaces(&mut self) {
+        #[cfg(feature = "render")]
+        crate::glfw_render::release_all_surfaces(&mut self.app);
+    }
+}
+
+impl Drop for GlfwAppRunner {
+    fn drop(&mut self) {
+        self.release_surfaces();
+    }
+}
+
+fn any_window_focused(world: &World) -> bool {
+    world
+        .resource::<Windows>()
+        .iter()
+        .any(|window| window.is_focused())
+}
+
+fn frame_rate_limit(world: &World) -> Option<f64> {
+    let settings = world.resource::<GlfwSettings>();
+    if unsafe { world.non_send_resource::<GlfwWindows>().all_minimized() } {
+        settings.minimized_frame_rate_limit
+    } else if any_window_focused(world) {
+        settings.focused_frame_rate_limit
+    } else {
+        settings.unfocused_frame_rate_limit
+    }
+}
+
+/// Called by the window refresh callback.
+///
+/// GLFW forbids creating and destroying windows from callbacks, that is left to the next
+/// [`GlfwAppRunner::update`].
+pub(crate) unsafe fn update_from_refresh() {
+    // taken for the duration of the update, callbacks may be called synchronously by GLFW functions
+    let runner = REFRESH_RUNNER.with(|refresh_runner| refresh_runner.replace(ptr::null_mut()));
+    if runner.is_null() {
+        return;
+    }
+
+    (*runner).send_events();
+    (*runner).update_app();
+    let mut create_window_events = (*runner).app.world.resource_mut::<Events<CreateWindow>>();
+    (*runner)
+        .pending_create_window_events
+        .extend(create_window_events.drain());
+    if !(*runner).should_exit {
+        (*runner).finish_frame();
+        REFRESH_RUNNER.with(|refresh_runner| refresh_runner.set(runner));
+    }
+}
+
+// no references into the runner may be held while waiting, refresh callbacks may update the app
+unsafe fn wait_for_events(runner: *mut GlfwAppRunner) {
+    let world = || &(*runner).app.world;
+    let paused = || {
+        let glfw_windows = world().non_send_resource::<GlfwWindows>();
+        glfw_windows.all_minimized()
+            && !glfw_windows.close_requested()
+            && !(*runner).proxy.take_wake_up()
+    };
+
+    if world().resource::<GlfwSettings>().pause_when_minimized
+        && world().non_send_resource::<GlfwWindows>().all_minimized()
+    {
+        // the app still has to handle close requests and explicit wake ups
+        while paused() {
+            glfwWaitEvents();
+        }
+
+        return;
+    }
+
+    let settings = world().resource::<GlfwSettings>();
+    let update_mode = if any_window_focused(world()) {
+        settings.focused_mode
+    } else {
+        settings.unfocused_mode
+    };
+
+    let (max_wait, low_power) = match update_mode {
+        _ if (*runner).redraw_requested => return glfwPollEvents(),
+        GlfwUpdateMode::Continuous => return glfwPollEvents(),
+        GlfwUpdateMode::Reactive { max_wait } => (max_wait, false),
+        GlfwUpdateMode::ReactiveLowPower { max_wait } => (max_wait, true),
+    };
+
+    loop {
+        match (*runner).last_update.checked_add(max_wait) {
+            Some(deadline) => {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                if timeout.is_zero() {
+                    return glfwPollEvents();
+                }
+
+                glfwWaitEventsTimeout(timeout.as_secs_f64());
+            }
+            None => glfwWaitEvents(),
+        }
+
+        if !low_power
+            || (*runner).should_exit
+            || (*runner).redraw_requested
+            || (*runner).proxy.take_wake_up()
+            || world()
+                .non_send_resource::<GlfwWindows>()
+                .has_pending_events()
+        {
+            return;
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use bevy::{prelude::*, window::WindowId};
+use glfw_bindgen::*;
+use std::{
+    cell::Cell,
+    ffi::{c_char, c_int, CStr},
+    fmt, mem,
+    sync::Mutex,
+};
+
+// filled by the error callback, which may be called from any thread
+static PENDING_ERRORS: Mutex<Vec<GlfwErrorEvent>> = Mutex::new(Vec::new());
+
+thread_local! {
+    // window the GLFW calls currently being made are about
+    static CURRENT_WINDOW: Cell<Option<WindowId>> = const { Cell::new(None) };
+    // errors that are expected and handled through glfwGetError
+    static SILENCED: Cell<bool> = const { Cell::new(false) };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlfwError {
+    NotInitialized,
+    NoCurrentContext,
+    InvalidEnum,
+    InvalidValue,
+    OutOfMemory,
+    ApiUnavailable,
+    VersionUnavailable,
+    PlatformError,
+    FormatUnavailable,
+    NoWindowContext,
+    CursorUnavailable,
+    FeatureUnavailable,
+    FeatureUnimplemented,
+    PlatformUnavailable,
+    /// Error code unknown to this version of `bevy_glfw`.
+    Unknown(i32),
+}
+
+impl GlfwError {
+    pub fn from_code(code: c_int) -> GlfwError {
+        match code as u32 {
+            GLFW_NOT_INITIALIZED => GlfwError::NotInitialized,
+            GLFW_NO_CURRENT_CONTEXT => GlfwError::NoCurrentContext,
+            GLFW_INVALID_ENUM => GlfwError::InvalidEnum,
+            GLFW_INVALID_VALUE => GlfwError::InvalidValue,
+            GLFW_OUT_OF_MEMORY => GlfwError::OutOfMemory,
+            GLFW_API_UNAVAILABLE => GlfwError::ApiUnavailable,
+            GLFW_VERSION_UNAVAILABLE => GlfwError::VersionUnavailable,
+            GLFW_PLATFORM_ERROR => GlfwError::PlatformError,
+            GLFW_FORMAT_UNAVAILABLE => GlfwError::FormatUnavailable,
+            GLFW_NO_WINDOW_CONTEXT => GlfwError::NoWindowContext,
+            GLFW_CURSOR_UNAVAILABLE => GlfwError::CursorUnavailable,
+            GLFW_FEATURE_UNAVAILABLE => GlfwError::FeatureUnavailable,
+            GLFW_FEATURE_UNIMPLEMENTED => GlfwError::FeatureUnimplemented,
+            GLFW_PLATFORM_UNAVAILABLE => GlfwError::PlatformUnavailable,
+            _ => GlfwError::Unknown(code),
+        }
+    }
+
+    pub fn code(self) -> c_int {
+        (match self {
+            GlfwError::NotInitialized => GLFW_NOT_INITIALIZED,
+            GlfwError::NoCurrentContext => GLFW_NO_CURRENT_CONTEXT,
+            GlfwError::InvalidEnum => GLFW_INVALID_ENUM,
+            GlfwError::InvalidValue => GLFW_INVALID_VALUE,
+            GlfwError::OutOfMemory => GLFW_OUT_OF_MEMORY,
+            GlfwError::ApiUnavailable => GLFW_API_UNAVAILABLE,
+            GlfwError::VersionUnavailable => GLFW_VERSION_UNAVAILABLE,
+            GlfwError::PlatformError => GLFW_PLATFORM_ERROR,
+            GlfwError::FormatUnavailable => GLFW_FORMAT_UNAVAILABLE,
+            GlfwError::NoWindowContext => GLFW_NO_WINDOW_CONTEXT,
+            GlfwError::CursorUnavailable => GLFW_CURSOR_UNAVAILABLE,
+            GlfwError::FeatureUnavailable => GLFW_FEATURE_UNAVAILABLE,
+            GlfwError::FeatureUnimplemented => GLFW_FEATURE_UNIMPLEMENTED,
+            GlfwError::PlatformUnavailable => GLFW_PLATFORM_UNAVAILABLE,
+            GlfwError::Unknown(code) => return code,
+        }) as _
+    }
+
+    /// Whether the error only means a requested feature isn't available on this platform.
+    pub fn is_unsupported(self) -> bool {
+        matches!(
+            self,
+            GlfwError::CursorUnavailable
+                | GlfwError::FeatureUnavailable
+                | GlfwError::FeatureUnimplemented
+        )
+    }
+}
+
+impl fmt::Display for GlfwError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?} (0x{:08X})", self.code())
+    }
+}
+
+/// Sent for every error reported by GLFW.
+#[derive(Debug, Clone)]
+pub struct GlfwErrorEvent {
+    pub error: GlfwError,
+    pub description: String,
+    /// Window the failing call was made for, if known.
+    pub window: Option<WindowId>,
+}
+
+pub(crate) unsafe extern "C" fn glfw_error_callback(error_code: c_int, description: *const c_char) {
+    if SILENCED.with(Cell::get) {
+        return;
+    }
+
+    let error = GlfwError::from_code(error_code);
+    let description = CStr::from_ptr(description).to_string_lossy().into_owned();
+    let window = CURRENT_WINDOW.with(Cell::get);
+
+    let window_info = window.map_or_else(String::new, |id| format!(" (window {id})"));
+    if error.is_unsupported() {
+        warn!("GLFW error {error}{window_info}: {description}");
+    } else {
+        error!("GLFW error {error}{window_info}: {description}");
+    }
+
+    PENDING_ERRORS.lock().unwrap().push(GlfwErrorEvent {
+        error,
+        description,
+        window,
+    });
+}
+
+/// Attributes GLFW errors to a window until dropped.
+pub(crate) struct WindowErrorScope(Option<WindowId>);
+
+impl WindowErrorScope {
+    pub fn new(window_id: WindowId) -> Self {
+        WindowErrorScope(CURRENT_WINDOW.with(|current| current.replace(Some(window_id))))
+    }
+}
+
+impl Drop for WindowErrorScope {
+    fn drop(&mut self) {
+        CURRENT_WINDOW.with(|current| current.set(self.0));
+    }
+}
+
+/// Neither logs GLFW errors nor sends them as [`GlfwErrorEvent`]s until dropped.
+pub(crate) struct SilentErrorScope(bool);
+
+impl SilentErrorScope {
+    pub fn new() -> Self {
+        SilentErrorScope(SILENCED.with(|silenced| silenced.replace(true)))
+    }
+}
+
+impl Drop for SilentErrorScope {
+    fn drop(&mut self) {
+        SILENCED.with(|silenced| silenced.set(self.0));
+    }
+}
+
+pub(crate) fn send_error_events(world: &mut World) {
+    let errors = mem::take(&mut *PENDING_ERRORS.lock().unwrap());
+    if !errors.is_empty() {
+        world
+            .resource_mut::<Events<GlfwErrorEvent>>()
+            .extend(errors);
+    }
+}
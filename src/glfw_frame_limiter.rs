@@ -0,0 +1,81 @@
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticId, Diagnostics},
+    prelude::*,
+};
+use std::{
+    hint, thread,
+    time::{Duration, Instant},
+};
+
+// sleeping is only accurate to a few milliseconds on some platforms, spin for the rest
+const SPIN_DURATION: Duration = Duration::from_millis(2);
+
+/// Adds diagnostics for the frame rate limit of [`glfw_runner`](crate::glfw_runner).
+#[derive(Default)]
+pub struct GlfwFrameLimiterDiagnosticsPlugin;
+
+impl Plugin for GlfwFrameLimiterDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(Self::setup_system);
+    }
+}
+
+impl GlfwFrameLimiterDiagnosticsPlugin {
+    /// Achieved frame time, including the time spent waiting for the frame rate limit.
+    pub const FRAME_TIME: DiagnosticId =
+        DiagnosticId::from_u128(312373557261743566454107090785849997712);
+    /// Time spent waiting for the frame rate limit.
+    pub const LIMITER_WAIT_TIME: DiagnosticId =
+        DiagnosticId::from_u128(189804493483562377564973689379097213384);
+
+    pub fn setup_system(mut diagnostics: ResMut<Diagnostics>) {
+        diagnostics.add(Diagnostic::new(Self::FRAME_TIME, "glfw_frame_time", 20).with_suffix("s"));
+        diagnostics.add(
+            Diagnostic::new(Self::LIMITER_WAIT_TIME, "glfw_frame_limiter_wait_time", 20)
+                .with_suffix("s"),
+        );
+    }
+}
+
+pub(crate) struct FrameLimiter {
+    frame_start: Instant,
+}
+
+impl FrameLimiter {
+    pub fn new() -> Self {
+        FrameLimiter {
+            frame_start: Instant::now(),
+        }
+    }
+
+    /// Waits until the current frame took at least `1 / limit` seconds.
+    pub fn finish_frame(&mut self, world: &mut World, limit: Option<f64>) {
+        let wait_start = Instant::now();
+        if let Some(limit) = limit.filter(|&limit| limit > 0.0) {
+            let deadline = self.frame_start + Duration::from_secs_f64(1.0 / limit);
+            if let Some(sleep_duration) = deadline
+                .checked_duration_since(wait_start)
+                .and_then(|remaining| remaining.checked_sub(SPIN_DURATION))
+            {
+                thread::sleep(sleep_duration);
+            }
+
+            while Instant::now() < deadline {
+                hint::spin_loop();
+            }
+        }
+
+        let frame_end = Instant::now();
+        if let Some(mut diagnostics) = world.get_resource_mut::<Diagnostics>() {
+            diagnostics.add_measurement(GlfwFrameLimiterDiagnosticsPlugin::FRAME_TIME, || {
+                (frame_end - self.frame_start).as_secs_f64()
+            });
+            diagnostics
+                .add_measurement(GlfwFrameLimiterDiagnosticsPlugin::LIMITER_WAIT_TIME, || {
+                    (frame_end - wait_start).as_secs_f64()
+                });
+        }
+
+        self.frame_start = frame_end;
+    }
+}
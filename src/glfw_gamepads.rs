@@ -0,0 +1,336 @@
+use crate::{
+    glfw_errors::SilentErrorScope, glfw_windows::MainThread, GlfwJoystickConnection,
+    GlfwJoystickConnectionEvent,
+};
+#[cfg(feature = "asset")]
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+};
+use bevy::{
+    ecs::system::SystemParam,
+    input::{
+        gamepad::{GamepadAxisType, GamepadButtonType, GamepadEventRaw, GamepadEventType},
+        InputSystem,
+    },
+    prelude::*,
+};
+use glfw_bindgen::*;
+use std::{
+    ffi::{c_int, CString},
+    ptr, str,
+};
+
+// longer lines are skipped by GLFW
+const MAX_MAPPING_LENGTH: usize = 1023;
+
+const BUTTONS: [(u32, GamepadButtonType); 15] = [
+    (GLFW_GAMEPAD_BUTTON_A, GamepadButtonType::South),
+    (GLFW_GAMEPAD_BUTTON_B, GamepadButtonType::East),
+    (GLFW_GAMEPAD_BUTTON_X, GamepadButtonType::West),
+    (GLFW_GAMEPAD_BUTTON_Y, GamepadButtonType::North),
+    (
+        GLFW_GAMEPAD_BUTTON_LEFT_BUMPER,
+        GamepadButtonType::LeftTrigger,
+    ),
+    (
+        GLFW_GAMEPAD_BUTTON_RIGHT_BUMPER,
+        GamepadButtonType::RightTrigger,
+    ),
+    (GLFW_GAMEPAD_BUTTON_BACK, GamepadButtonType::Select),
+    (GLFW_GAMEPAD_BUTTON_START, GamepadButtonType::Start),
+    (GLFW_GAMEPAD_BUTTON_GUIDE, GamepadButtonType::Mode),
+    (GLFW_GAMEPAD_BUTTON_LEFT_THUMB, GamepadButtonType::LeftThumb),
+    (
+        GLFW_GAMEPAD_BUTTON_RIGHT_THUMB,
+        GamepadButtonType::RightThumb,
+    ),
+    (GLFW_GAMEPAD_BUTTON_DPAD_UP, GamepadButtonType::DPadUp),
+    (GLFW_GAMEPAD_BUTTON_DPAD_RIGHT, GamepadButtonType::DPadRight),
+    (GLFW_GAMEPAD_BUTTON_DPAD_DOWN, GamepadButtonType::DPadDown),
+    (GLFW_GAMEPAD_BUTTON_DPAD_LEFT, GamepadButtonType::DPadLeft),
+];
+
+// GLFW's Y axes point down, Bevy's up
+const AXES: [(u32, GamepadAxisType, f32); 4] = [
+    (GLFW_GAMEPAD_AXIS_LEFT_X, GamepadAxisType::LeftStickX, 1.0),
+    (GLFW_GAMEPAD_AXIS_LEFT_Y, GamepadAxisType::LeftStickY, -1.0),
+    (GLFW_GAMEPAD_AXIS_RIGHT_X, GamepadAxisType::RightStickX, 1.0),
+    (
+        GLFW_GAMEPAD_AXIS_RIGHT_Y,
+        GamepadAxisType::RightStickY,
+        -1.0,
+    ),
+];
+
+// analog triggers are buttons in Bevy
+const TRIGGERS: [(u32, GamepadButtonType); 2] = [
+    (
+        GLFW_GAMEPAD_AXIS_LEFT_TRIGGER,
+        GamepadButtonType::LeftTrigger2,
+    ),
+    (
+        GLFW_GAMEPAD_AXIS_RIGHT_TRIGGER,
+        GamepadButtonType::RightTrigger2,
+    ),
+];
+
+const NEUTRAL_STATE: GLFWgamepadstate = GLFWgamepadstate {
+    buttons: [GLFW_RELEASE as _; 15],
+    axes: [0.0, 0.0, 0.0, 0.0, -1.0, -1.0],
+};
+
+/// Sends the input of joysticks with a gamepad mapping as [`GamepadEventRaw`]s.
+///
+/// The id of a [`Gamepad`] is its GLFW joystick id, which stays the same until it is
+/// disconnected.
+#[derive(Default)]
+pub struct GlfwGamepadPlugin;
+
+impl Plugin for GlfwGamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<GlfwGamepadMappingError>()
+            .add_system_to_stage(CoreStage::PreUpdate, poll_gamepads.before(InputSystem));
+
+        #[cfg(feature = "asset")]
+        if app.world.contains_resource::<AssetServer>() {
+            app.add_asset::<GlfwGamepadMappingsAsset>()
+                .init_asset_loader::<GlfwGamepadMappingsLoader>()
+                .add_system_to_stage(
+                    CoreStage::PreUpdate,
+                    update_gamepad_mappings_from_assets.before(poll_gamepads),
+                );
+        } else {
+            warn!("GlfwGamepadPlugin added before AssetPlugin, gamepad mapping assets won't load");
+        }
+    }
+}
+
+/// Sent for every line of a gamepad mapping database that GLFW failed to parse.
+#[derive(Debug, Clone)]
+pub struct GlfwGamepadMappingError {
+    /// Starting at 1.
+    pub line_number: usize,
+    pub line: String,
+}
+
+/// Adds gamepad mappings to the ones built into GLFW.
+#[derive(SystemParam)]
+pub struct GlfwGamepadMappings<'w, 's> {
+    _main_thread: MainThread<'w>,
+    mapping_errors: EventWriter<'w, 's, GlfwGamepadMappingError>,
+}
+
+impl<'w, 's> GlfwGamepadMappings<'w, 's> {
+    /// Adds or replaces the mappings in the format of SDL's `gamecontrollerdb.txt`.
+    ///
+    /// Mappings for other platforms are skipped, lines that GLFW can't parse, including ones
+    /// using output modifiers like `+leftx:`, are sent as [`GlfwGamepadMappingError`]s.
+    pub fn update(&mut self, mappings: &str) {
+        for (index, line) in mappings.lines().enumerate() {
+            // GLFW ignores everything else, like comments
+            if !line.starts_with(|c: char| c.is_ascii_hexdigit()) {
+                continue;
+            }
+
+            let parsed = match CString::new(line) {
+                _ if has_output_modifiers(line, mapping_platform()) => false,
+                Ok(line) if line.as_bytes().len() <= MAX_MAPPING_LENGTH => unsafe {
+                    // malformed GUIDs and names are only reported through the error code
+                    let _silent_error_scope = SilentErrorScope::new();
+                    glfwGetError(ptr::null_mut());
+                    glfwUpdateGamepadMappings(line.as_ptr());
+                    glfwGetError(ptr::null_mut()) == GLFW_NO_ERROR as c_int
+                },
+                _ => false,
+            };
+
+            if !parsed {
+                self.mapping_errors.send(GlfwGamepadMappingError {
+                    line_number: index + 1,
+                    line: line.to_owned(),
+                });
+            }
+        }
+    }
+}
+
+// GLFW silently rejects mappings with output modifiers, which is only an error if the mapping is
+// meant for this platform
+fn has_output_modifiers(line: &str, platform: &str) -> bool {
+    let fields = || line.split(',').skip(2);
+    let other_platform = matches!(
+        fields().find_map(|field| field.strip_prefix("platform:")),
+        Some(line_platform) if !line_platform.starts_with(platform)
+    );
+
+    !other_platform && fields().any(|field| field.starts_with(['+', '-']))
+}
+
+// platform name GLFW accepts mappings for, the null platform accepts all
+fn mapping_platform() -> &'static str {
+    if unsafe { glfwGetPlatform() } == GLFW_PLATFORM_NULL as c_int {
+        ""
+    } else if cfg!(target_os = "windows") {
+        "Windows"
+    } else if cfg!(target_os = "macos") {
+        "Mac OS X"
+    } else {
+        "Linux"
+    }
+}
+
+/// SDL gamepad mapping database, loaded from files ending in `.gamecontrollerdb.txt`.
+///
+/// Bevy only matches extensions after a dot, so SDL's `gamecontrollerdb.txt` has to be renamed,
+/// e.g. to `sdl.gamecontrollerdb.txt`.
+///
+/// Loaded and modified assets are added to the mappings, hot reloading them requires
+/// [`AssetServerSettings::watch_for_changes`](bevy::asset::AssetServerSettings::watch_for_changes).
+#[cfg(feature = "asset")]
+#[derive(Debug, TypeUuid)]
+#[uuid = "76fc592d-2171-46fb-ac6e-1e1e842d6df6"]
+pub struct GlfwGamepadMappingsAsset(pub String);
+
+#[cfg(feature = "asset")]
+#[derive(Default)]
+struct GlfwGamepadMappingsLoader;
+
+#[cfg(feature = "asset")]
+impl AssetLoader for GlfwGamepadMappingsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let mappings = str::from_utf8(bytes)?.to_owned();
+            load_context.set_default_asset(LoadedAsset::new(GlfwGamepadMappingsAsset(mappings)));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["gamecontrollerdb.txt"]
+    }
+}
+
+#[cfg(feature = "asset")]
+fn update_gamepad_mappings_from_assets(
+    mut asset_events: EventReader<AssetEvent<GlfwGamepadMappingsAsset>>,
+    assets: Res<Assets<GlfwGamepadMappingsAsset>>,
+    mut gamepad_mappings: GlfwGamepadMappings,
+) {
+    for event in asset_events.iter() {
+        if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = event {
+            if let Some(asset) = assets.get(handle) {
+                gamepad_mappings.update(&asset.0);
+            }
+        }
+    }
+}
+
+type GamepadStates = [Option<GLFWgamepadstate>; GLFW_JOYSTICK_LAST as usize + 1];
+
+fn poll_gamepads(
+    _main_thread: MainThread,
+    mut states: Local<GamepadStates>,
+    mut joystick_connection_events: EventReader<GlfwJoystickConnectionEvent>,
+    mut gamepad_events: EventWriter<GamepadEventRaw>,
+) {
+    // the slot may already be reused by another joystick
+    for event in joystick_connection_events.iter() {
+        if let GlfwJoystickConnection::Disconnected { .. } = event.connection {
+            if states[event.id].take().is_some() {
+                gamepad_events.send(GamepadEventRaw::new(
+                    Gamepad::new(event.id),
+                    GamepadEventType::Disconnected,
+                ));
+            }
+        }
+    }
+
+    for (jid, previous_state) in states.iter_mut().enumerate() {
+        let gamepad = Gamepad::new(jid);
+        let mut state = NEUTRAL_STATE;
+        if unsafe { glfwGetGamepadState(jid as _, &mut state) } != GLFW_TRUE as c_int {
+            if previous_state.take().is_some() {
+                gamepad_events.send(GamepadEventRaw::new(
+                    gamepad,
+                    GamepadEventType::Disconnected,
+                ));
+            }
+
+            continue;
+        }
+
+        let previous_state = previous_state.get_or_insert_with(|| {
+            gamepad_events.send(GamepadEventRaw::new(gamepad, GamepadEventType::Connected));
+            NEUTRAL_STATE
+        });
+
+        for (button, button_type) in BUTTONS {
+            let pressed = state.buttons[button as usize];
+            if pressed != previous_state.buttons[button as usize] {
+                let value = if pressed == GLFW_PRESS as u8 {
+                    1.0
+                } else {
+                    0.0
+                };
+                gamepad_events.send(GamepadEventRaw::new(
+                    gamepad,
+                    GamepadEventType::ButtonChanged(button_type, value),
+                ));
+            }
+        }
+
+        for (axis, axis_type, direction) in AXES {
+            let value = state.axes[axis as usize];
+            if value != previous_state.axes[axis as usize] {
+                gamepad_events.send(GamepadEventRaw::new(
+                    gamepad,
+                    GamepadEventType::AxisChanged(axis_type, value * direction),
+                ));
+            }
+        }
+
+        for (axis, button_type) in TRIGGERS {
+            let value = state.axes[axis as usize];
+            if value != previous_state.axes[axis as usize] {
+                gamepad_events.send(GamepadEventRaw::new(
+                    gamepad,
+                    GamepadEventType::ButtonChanged(button_type, (value + 1.0) / 2.0),
+                ));
+            }
+        }
+
+        *previous_state = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::has_output_modifiers;
+
+    const GUID: &str = "030000005e0400008e02000014010000";
+
+    #[test]
+    fn output_modifiers() {
+        let line = |fields: &str| format!("{GUID},Test,{fields}");
+        assert!(!has_output_modifiers(&line("a:b0,leftx:+a0,"), "Linux"));
+        assert!(has_output_modifiers(&line("a:b0,+leftx:a0,"), "Linux"));
+        assert!(has_output_modifiers(
+            &line("-lefty:a1,platform:Linux,"),
+            "Linux"
+        ));
+        assert!(!has_output_modifiers(
+            &line("-lefty:a1,platform:Windows,"),
+            "Linux"
+        ));
+        // the null platform accepts mappings for every platform
+        assert!(has_output_modifiers(
+            &line("-lefty:a1,platform:Windows,"),
+            ""
+        ));
+    }
+}
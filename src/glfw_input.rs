@@ -0,0 +1,169 @@
+use crate::{
+    glfw_errors::SilentErrorScope,
+    glfw_windows::MainThread,
+    keys::{glfw_key_to_keycode, keycode_to_glfw_key},
+};
+use bevy::{
+    ecs::{system::SystemParam, world::WorldCell},
+    input::keyboard::KeyboardInput,
+    prelude::*,
+    window::{WindowFocused, WindowId},
+};
+use glfw_bindgen::*;
+use std::{
+    ffi::{c_int, CStr},
+    marker::PhantomData,
+};
+
+/// Modifier keys held during the last key or mouse button event, as reported by GLFW.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GlfwModifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+    pub super_: bool,
+    /// Requires [`GlfwSettings::lock_key_mods`](crate::GlfwSettings::lock_key_mods).
+    pub caps_lock: bool,
+    /// Requires [`GlfwSettings::lock_key_mods`](crate::GlfwSettings::lock_key_mods).
+    pub num_lock: bool,
+}
+
+impl GlfwModifiers {
+    pub fn from_glfw(mods: c_int) -> Self {
+        let has = |modifier: u32| mods & modifier as c_int != 0;
+        GlfwModifiers {
+            shift: has(GLFW_MOD_SHIFT),
+            control: has(GLFW_MOD_CONTROL),
+            alt: has(GLFW_MOD_ALT),
+            super_: has(GLFW_MOD_SUPER),
+            caps_lock: has(GLFW_MOD_CAPS_LOCK),
+            num_lock: has(GLFW_MOD_NUM_LOCK),
+        }
+    }
+}
+
+/// Sent when [`GlfwModifiers`] changes.
+#[derive(Debug, Clone)]
+pub struct GlfwModifiersChanged {
+    /// Window that received the event carrying the new modifiers.
+    pub id: WindowId,
+    pub modifiers: GlfwModifiers,
+}
+
+/// Sent when Caps Lock or Num Lock is toggled, requires
+/// [`GlfwSettings::lock_key_mods`](crate::GlfwSettings::lock_key_mods).
+#[derive(Debug, Clone)]
+pub struct GlfwLockKeysChanged {
+    pub caps_lock: bool,
+    pub num_lock: bool,
+}
+
+/// Like [`KeyboardInput`], which is still sent, but tells auto-repeated presses apart.
+#[derive(Debug, Clone)]
+pub struct GlfwKeyboardInput {
+    pub id: WindowId,
+    pub input: KeyboardInput,
+    /// Sent by the OS while the key is held down, `input.state` is always pressed.
+    pub repeat: bool,
+}
+
+pub(crate) fn update_modifiers(world: &WorldCell, id: WindowId, modifiers: GlfwModifiers) {
+    let mut current = world.resource_mut::<GlfwModifiers>();
+    if *current == modifiers {
+        return;
+    }
+
+    if (current.caps_lock, current.num_lock) != (modifiers.caps_lock, modifiers.num_lock) {
+        world
+            .resource_mut::<Events<GlfwLockKeysChanged>>()
+            .send(GlfwLockKeysChanged {
+                caps_lock: modifiers.caps_lock,
+                num_lock: modifiers.num_lock,
+            });
+    }
+
+    *current = modifiers;
+    world
+        .resource_mut::<Events<GlfwModifiersChanged>>()
+        .send(GlfwModifiersChanged { id, modifiers });
+}
+
+/// Layout dependent names of printable keys, e.g. `KeyCode::W` is named "z" on AZERTY layouts.
+#[derive(SystemParam)]
+pub struct GlfwKeyNames<'w, 's> {
+    _main_thread: MainThread<'w>,
+    #[system_param(ignore)]
+    _marker: PhantomData<&'s ()>,
+}
+
+impl<'w, 's> GlfwKeyNames<'w, 's> {
+    /// Name of the key, `None` if it isn't printable.
+    pub fn key_name(&self, key_code: KeyCode) -> Option<String> {
+        glfw_keys(key_code).find_map(|key| unsafe { glfw_key_name(key) })
+    }
+
+    /// Name of the key with the platform specific scan code, `None` if it isn't printable or
+    /// unknown to GLFW.
+    pub fn scan_code_name(&self, scan_code: u32) -> Option<String> {
+        // unknown scan codes raise an error
+        let _silent_error_scope = SilentErrorScope::new();
+        unsafe { key_name(GLFW_KEY_UNKNOWN, scan_code as _) }
+    }
+
+    /// Platform specific scan code of the key.
+    pub fn scan_code(&self, key_code: KeyCode) -> Option<u32> {
+        glfw_keys(key_code)
+            .map(|key| unsafe { glfwGetKeyScancode(key) })
+            .find(|&scan_code| scan_code != -1)
+            .map(|scan_code| scan_code as _)
+    }
+
+    fn layout(&self) -> Vec<Option<String>> {
+        (GLFW_KEY_SPACE as c_int..=GLFW_KEY_LAST as c_int)
+            .filter(|&key| glfw_key_to_keycode(key).is_some())
+            .map(|key| unsafe { glfw_key_name(key) })
+            .collect()
+    }
+}
+
+// the non-US backslash key is either WORLD key, depending on the platform
+fn glfw_keys(key_code: KeyCode) -> impl Iterator<Item = c_int> {
+    let world_2 = (key_code == KeyCode::Oem102).then_some(GLFW_KEY_WORLD_2 as c_int);
+    keycode_to_glfw_key(key_code).into_iter().chain(world_2)
+}
+
+// keys without a scan code on the current platform raise an error
+unsafe fn glfw_key_name(key: c_int) -> Option<String> {
+    if glfwGetKeyScancode(key) == -1 {
+        return None;
+    }
+
+    key_name(key, 0)
+}
+
+unsafe fn key_name(key: c_int, scan_code: c_int) -> Option<String> {
+    let name = glfwGetKeyName(key, scan_code);
+    (!name.is_null()).then(|| CStr::from_ptr(name).to_string_lossy().into_owned())
+}
+
+/// Sent when the names in [`GlfwKeyNames`] changed.
+///
+/// GLFW doesn't report layout changes, the names are compared whenever a window gains focus.
+#[derive(Debug, Clone)]
+pub struct GlfwKeyboardLayoutChanged;
+
+pub(crate) fn detect_keyboard_layout_change(
+    key_names: GlfwKeyNames,
+    mut window_focused_events: EventReader<WindowFocused>,
+    mut layout_changed_events: EventWriter<GlfwKeyboardLayoutChanged>,
+    mut layout: Local<Option<Vec<Option<String>>>>,
+) {
+    let layout = layout.get_or_insert_with(|| key_names.layout());
+    if window_focused_events.iter().any(|event| event.focused) {
+        let current_layout = key_names.layout();
+        if *layout != current_layout {
+            *layout = current_layout;
+            layout_changed_events.send(GlfwKeyboardLayoutChanged);
+        }
+    }
+}
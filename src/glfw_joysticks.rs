@@ -0,0 +1,242 @@
+use crate::glfw_windows::MainThread;
+use bevy::{prelude::*, utils::HashMap};
+use glfw_bindgen::*;
+use std::{
+    ffi::{c_char, c_int, CStr},
+    mem, slice,
+    sync::Mutex,
+};
+
+// filled by the joystick callback, which may also be called from joystick functions
+static PENDING_CONNECTION_EVENTS: Mutex<Vec<GlfwJoystickConnectionEvent>> = Mutex::new(Vec::new());
+
+type JoystickIdentity = Option<(String, String)>;
+const NO_JOYSTICK: JoystickIdentity = None;
+// name and GUID by joystick id, GLFW only reports them for connected joysticks
+static JOYSTICK_IDENTITIES: Mutex<[JoystickIdentity; GLFW_JOYSTICK_LAST as usize + 1]> =
+    Mutex::new([NO_JOYSTICK; GLFW_JOYSTICK_LAST as usize + 1]);
+
+#[derive(Debug, Clone)]
+pub enum GlfwJoystickConnection {
+    Connected {
+        name: String,
+        /// SDL compatible GUID.
+        guid: String,
+        /// Whether the joystick has a gamepad mapping.
+        is_gamepad: bool,
+    },
+    Disconnected {
+        name: String,
+        /// SDL compatible GUID.
+        guid: String,
+    },
+}
+
+/// Sent when a joystick is connected or disconnected, and for the joysticks connected at startup.
+#[derive(Debug, Clone)]
+pub struct GlfwJoystickConnectionEvent {
+    /// GLFW joystick id, also the id of the [`Gamepad`] if it has a gamepad mapping.
+    pub id: usize,
+    pub connection: GlfwJoystickConnection,
+}
+
+/// Tracks the raw state of all joysticks in [`GlfwJoysticks`], including ones without a
+/// gamepad mapping.
+#[derive(Default)]
+pub struct GlfwJoystickPlugin;
+
+impl Plugin for GlfwJoystickPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GlfwJoysticks>()
+            .add_event::<GlfwJoystickEvent>()
+            .add_system_to_stage(CoreStage::PreUpdate, poll_joysticks);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GlfwJoystick {
+    pub name: String,
+    /// SDL compatible GUID.
+    pub guid: String,
+    /// From -1 to 1.
+    pub axes: Vec<f32>,
+    /// Includes the hats, unless [`GlfwSettings::joystick_hat_buttons`](crate::GlfwSettings::joystick_hat_buttons)
+    /// is disabled.
+    pub buttons: Vec<bool>,
+    /// Direction of each hat, with positive Y pointing up.
+    pub hats: Vec<IVec2>,
+}
+
+/// Connected joysticks by GLFW joystick id.
+#[derive(Debug, Default)]
+pub struct GlfwJoysticks {
+    joysticks: HashMap<usize, GlfwJoystick>,
+}
+
+impl GlfwJoysticks {
+    pub fn get(&self, id: usize) -> Option<&GlfwJoystick> {
+        self.joysticks.get(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &GlfwJoystick)> {
+        self.joysticks.iter().map(|(&id, joystick)| (id, joystick))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GlfwJoystickEventType {
+    AxisChanged(usize, f32),
+    ButtonChanged(usize, bool),
+    HatChanged(usize, IVec2),
+}
+
+/// Sent when a value in [`GlfwJoysticks`] changes.
+#[derive(Debug, Clone)]
+pub struct GlfwJoystickEvent {
+    pub id: usize,
+    pub event_type: GlfwJoystickEventType,
+}
+
+fn poll_joysticks(
+    _main_thread: MainThread,
+    mut joysticks: ResMut<GlfwJoysticks>,
+    mut joystick_connection_events: EventReader<GlfwJoystickConnectionEvent>,
+    mut joystick_events: EventWriter<GlfwJoystickEvent>,
+) {
+    for event in joystick_connection_events.iter() {
+        match &event.connection {
+            GlfwJoystickConnection::Connected { name, guid, .. } => {
+                joysticks.joysticks.insert(
+                    event.id,
+                    GlfwJoystick {
+                        name: name.clone(),
+                        guid: guid.clone(),
+                        axes: Vec::new(),
+                        buttons: Vec::new(),
+                        hats: Vec::new(),
+                    },
+                );
+            }
+            GlfwJoystickConnection::Disconnected { .. } => {
+                joysticks.joysticks.remove(&event.id);
+            }
+        }
+    }
+
+    for (&id, joystick) in joysticks.joysticks.iter_mut() {
+        let mut send = |event_type| joystick_events.send(GlfwJoystickEvent { id, event_type });
+        // the arrays are freed once a call detects that the joystick got disconnected
+        let axes = unsafe { joystick_values(glfwGetJoystickAxes, id) };
+        update_values(&mut joystick.axes, axes.iter().copied(), |index, value| {
+            send(GlfwJoystickEventType::AxisChanged(index, value))
+        });
+
+        let buttons = unsafe { joystick_values(glfwGetJoystickButtons, id) };
+        update_values(
+            &mut joystick.buttons,
+            buttons.iter().map(|&button| button == GLFW_PRESS as u8),
+            |index, pressed| send(GlfwJoystickEventType::ButtonChanged(index, pressed)),
+        );
+
+        let hats = unsafe { joystick_values(glfwGetJoystickHats, id) };
+        update_values(
+            &mut joystick.hats,
+            hats.iter().map(|&hat| hat_direction(hat)),
+            |index, direction| send(GlfwJoystickEventType::HatChanged(index, direction)),
+        );
+    }
+}
+
+// empty if the joystick got disconnected
+unsafe fn joystick_values<'a, T>(
+    get: unsafe extern "C" fn(c_int, *mut c_int) -> *const T,
+    id: usize,
+) -> &'a [T] {
+    let mut count = 0;
+    let values = get(id as _, &mut count);
+    if values.is_null() {
+        &[]
+    } else {
+        slice::from_raw_parts(values, count as _)
+    }
+}
+
+fn update_values<T: Copy + Default + PartialEq>(
+    values: &mut Vec<T>,
+    new_values: impl ExactSizeIterator<Item = T>,
+    mut changed: impl FnMut(usize, T),
+) {
+    values.resize(new_values.len(), T::default());
+    for (index, (value, new_value)) in values.iter_mut().zip(new_values).enumerate() {
+        if *value != new_value {
+            *value = new_value;
+            changed(index, new_value);
+        }
+    }
+}
+
+fn hat_direction(hat: u8) -> IVec2 {
+    let has = |direction: u32| (hat & direction as u8 != 0) as i32;
+    IVec2::new(
+        has(GLFW_HAT_RIGHT) - has(GLFW_HAT_LEFT),
+        has(GLFW_HAT_UP) - has(GLFW_HAT_DOWN),
+    )
+}
+
+pub(crate) unsafe extern "C" fn glfw_joystick_callback(jid: c_int, event: c_int) {
+    // joystick functions may call the callback again, the lock isn't held while calling them
+    let connection = match event as u32 {
+        GLFW_CONNECTED => {
+            let name = string(glfwGetJoystickName(jid));
+            let guid = string(glfwGetJoystickGUID(jid));
+            let is_gamepad = glfwJoystickIsGamepad(jid) == GLFW_TRUE as c_int;
+            JOYSTICK_IDENTITIES.lock().unwrap()[jid as usize] = Some((name.clone(), guid.clone()));
+            GlfwJoystickConnection::Connected {
+                name,
+                guid,
+                is_gamepad,
+            }
+        }
+        // the joystick is already marked as disconnected
+        GLFW_DISCONNECTED => {
+            let identity = JOYSTICK_IDENTITIES.lock().unwrap()[jid as usize].take();
+            let (name, guid) = identity.unwrap_or_default();
+            GlfwJoystickConnection::Disconnected { name, guid }
+        }
+        _ => return,
+    };
+
+    PENDING_CONNECTION_EVENTS
+        .lock()
+        .unwrap()
+        .push(GlfwJoystickConnectionEvent {
+            id: jid as _,
+            connection,
+        });
+}
+
+/// Reports the joysticks connected before the callback was set.
+pub(crate) unsafe fn connect_present_joysticks() {
+    for jid in GLFW_JOYSTICK_1 as c_int..=GLFW_JOYSTICK_LAST as c_int {
+        if glfwJoystickPresent(jid) == GLFW_TRUE as c_int {
+            glfw_joystick_callback(jid, GLFW_CONNECTED as _);
+        }
+    }
+}
+
+unsafe fn string(string: *const c_char) -> String {
+    if string.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(string).to_string_lossy().into_owned()
+    }
+}
+
+pub(crate) fn send_joystick_events(world: &mut World) {
+    let events = mem::take(&mut *PENDING_CONNECTION_EVENTS.lock().unwrap());
+    if !events.is_empty() {
+        world
+            .resource_mut::<Events<GlfwJoystickConnectionEvent>>()
+            .extend(events);
+    }
+}
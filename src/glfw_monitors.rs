@@ -0,0 +1,186 @@
+use crate::glfw_windows::{GlfwWindows, MainThread};
+use bevy::prelude::*;
+use glfw_bindgen::*;
+use std::{
+    ffi::{c_int, CStr},
+    slice,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+// set by the monitor callback, the supported video modes are only queried again afterwards
+static MONITORS_RECONFIGURED: AtomicBool = AtomicBool::new(true);
+
+/// Tracks the connected monitors in [`GlfwMonitors`], has to be added after
+/// [`GlfwPlugin`](crate::GlfwPlugin).
+#[derive(Default)]
+pub struct GlfwMonitorPlugin;
+
+impl Plugin for GlfwMonitorPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.world.contains_resource::<GlfwWindows>(),
+            "GlfwMonitorPlugin added before GlfwPlugin"
+        );
+
+        unsafe { glfwSetMonitorCallback(Some(glfw_monitor_callback)) };
+        app.insert_resource(unsafe { GlfwMonitors::new() })
+            .add_system_to_stage(CoreStage::PreUpdate, update_monitors);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlfwVideoMode {
+    pub size: UVec2,
+    pub red_bits: u32,
+    pub green_bits: u32,
+    pub blue_bits: u32,
+    pub refresh_rate: u32,
+}
+
+impl GlfwVideoMode {
+    fn from_glfw(video_mode: &GLFWvidmode) -> Self {
+        GlfwVideoMode {
+            size: UVec2::new(video_mode.width as _, video_mode.height as _),
+            red_bits: video_mode.redBits as _,
+            green_bits: video_mode.greenBits as _,
+            blue_bits: video_mode.blueBits as _,
+            refresh_rate: video_mode.refreshRate as _,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlfwMonitor {
+    pub name: String,
+    /// Position on the virtual screen, in screen coordinates.
+    pub position: IVec2,
+    /// In millimeters, may be zero or inaccurate.
+    pub physical_size: UVec2,
+    pub content_scale: Vec2,
+    /// Area not occupied by task bars or menu bars, in screen coordinates.
+    pub work_area_position: IVec2,
+    pub work_area_size: UVec2,
+    pub video_mode: Option<GlfwVideoMode>,
+    pub video_modes: Vec<GlfwVideoMode>,
+}
+
+impl GlfwMonitor {
+    unsafe fn new(monitor: *mut GLFWmonitor, video_modes: Vec<GlfwVideoMode>) -> Self {
+        let name = glfwGetMonitorName(monitor);
+        let name = if name.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(name).to_string_lossy().into_owned()
+        };
+
+        let mut position = IVec2::ZERO;
+        glfwGetMonitorPos(monitor, &mut position.x, &mut position.y);
+
+        let mut physical_size = IVec2::ZERO;
+        glfwGetMonitorPhysicalSize(monitor, &mut physical_size.x, &mut physical_size.y);
+
+        let mut content_scale = Vec2::ZERO;
+        glfwGetMonitorContentScale(monitor, &mut content_scale.x, &mut content_scale.y);
+
+        let mut work_area_position = IVec2::ZERO;
+        let mut work_area_size = IVec2::ZERO;
+        glfwGetMonitorWorkarea(
+            monitor,
+            &mut work_area_position.x,
+            &mut work_area_position.y,
+            &mut work_area_size.x,
+            &mut work_area_size.y,
+        );
+
+        GlfwMonitor {
+            name,
+            position,
+            physical_size: physical_size.as_uvec2(),
+            content_scale,
+            work_area_position,
+            work_area_size: work_area_size.as_uvec2(),
+            video_mode: glfwGetVideoMode(monitor)
+                .as_ref()
+                .map(GlfwVideoMode::from_glfw),
+            video_modes,
+        }
+    }
+}
+
+/// Connected monitors, updated every frame by [`GlfwMonitorPlugin`].
+///
+/// The first monitor is the primary monitor, the indices match
+/// [`MonitorSelection::Number`](bevy::window::MonitorSelection::Number).
+#[derive(Debug)]
+pub struct GlfwMonitors {
+    pub monitors: Vec<GlfwMonitor>,
+}
+
+impl GlfwMonitors {
+    pub fn primary(&self) -> Option<&GlfwMonitor> {
+        self.monitors.first()
+    }
+
+    // SAFETY: GLFW has to be initialized, and this has to be the main thread
+    unsafe fn new() -> Self {
+        let mut monitors = GlfwMonitors {
+            monitors: Vec::new(),
+        };
+        monitors.monitors = monitors.query();
+        monitors
+    }
+
+    // the supported video modes are reused unless the monitor config changed
+    unsafe fn query(&self) -> Vec<GlfwMonitor> {
+        let reconfigured = MONITORS_RECONFIGURED.swap(false, Ordering::Relaxed);
+        connected_monitors()
+            .iter()
+            .enumerate()
+            .map(|(index, &monitor)| {
+                let video_modes = match self.monitors.get(index) {
+                    Some(previous) if !reconfigured => previous.video_modes.clone(),
+                    _ => video_modes(monitor),
+                };
+
+                GlfwMonitor::new(monitor, video_modes)
+            })
+            .collect()
+    }
+}
+
+unsafe extern "C" fn glfw_monitor_callback(_monitor: *mut GLFWmonitor, _event: c_int) {
+    MONITORS_RECONFIGURED.store(true, Ordering::Relaxed);
+}
+
+// first monitor is the primary monitor
+// SAFETY: only valid until glfw is terminated or the monitor config changes
+pub(crate) unsafe fn connected_monitors() -> &'static [*mut GLFWmonitor] {
+    let mut monitor_count = 0;
+    let monitors_ptr = glfwGetMonitors(&mut monitor_count);
+    if monitors_ptr.is_null() {
+        return &[];
+    }
+
+    slice::from_raw_parts(monitors_ptr, monitor_count as _)
+}
+
+unsafe fn video_modes(monitor: *mut GLFWmonitor) -> Vec<GlfwVideoMode> {
+    let mut video_mode_count = 0;
+    let video_modes = glfwGetVideoModes(monitor, &mut video_mode_count);
+    if video_modes.is_null() {
+        return Vec::new();
+    }
+
+    slice::from_raw_parts(video_modes, video_mode_count as _)
+        .iter()
+        .map(GlfwVideoMode::from_glfw)
+        .collect()
+}
+
+fn update_monitors(_main_thread: MainThread, mut monitors: ResMut<GlfwMonitors>) {
+    let current_monitors = unsafe { monitors.query() };
+    // only trigger change detection on changes
+    if monitors.monitors != current_monitors {
+        monitors.monitors = current_monitors;
+    }
+}
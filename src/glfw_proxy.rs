@@ -0,0 +1,64 @@
+use bevy::{ecs::event::Event, prelude::*};
+use glfw_bindgen::*;
+use std::{
+    any::type_name,
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+type UserEvent = Box<dyn FnOnce(&mut World) + Send>;
+
+#[derive(Default)]
+struct ProxyState {
+    woken: AtomicBool,
+    events: Mutex<Vec<UserEvent>>,
+}
+
+/// Wakes up [`glfw_runner`](crate::glfw_runner) from any thread.
+///
+/// Clone it out of the world to hand it to background tasks.
+#[derive(Clone, Default)]
+pub struct GlfwEventLoopProxy(Arc<ProxyState>);
+
+impl GlfwEventLoopProxy {
+    /// Makes the runner update, even if it is waiting in a reactive update mode.
+    pub fn wake_up(&self) {
+        self.0.woken.store(true, Ordering::Release);
+        unsafe { glfwPostEmptyEvent() };
+    }
+
+    /// Sends `event` into the world before the next update and wakes up the runner.
+    pub fn send_event<E: Event>(&self, event: E) {
+        self.0
+            .events
+            .lock()
+            .unwrap()
+            .push(Box::new(|world: &mut World| {
+                match world.get_resource_mut::<Events<E>>() {
+                    Some(mut events) => events.send(event),
+                    None => warn!(
+                        "Dropping {} sent through GlfwEventLoopProxy, event was never added",
+                        type_name::<E>()
+                    ),
+                }
+            }));
+
+        self.wake_up();
+    }
+
+    pub(crate) fn take_wake_up(&self) -> bool {
+        self.0.woken.swap(false, Ordering::Acquire)
+    }
+
+    pub(crate) fn send_user_events(&self, world: &mut World) {
+        // the following update handles earlier wake ups, also in modes that don't wait for them
+        self.take_wake_up();
+        let events = mem::take(&mut *self.0.events.lock().unwrap());
+        for event in events {
+            event(world);
+        }
+    }
+}
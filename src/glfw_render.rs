@@ -0,0 +1,48 @@
+use bevy::{
+    prelude::*,
+    render::{view::WindowSurfaces, Extract, RenderApp, RenderStage},
+    window::{WindowClosed, WindowId},
+};
+use std::sync::{Arc, Mutex};
+
+struct ReleasedWindowSurfaces(Arc<Mutex<Vec<WindowId>>>);
+
+/// Makes the renderer release the surfaces of closed windows, returns where they're reported.
+///
+/// `None` if [`RenderPlugin`](bevy::render::RenderPlugin) wasn't added yet.
+pub(crate) fn setup_surface_release(app: &mut App) -> Option<Arc<Mutex<Vec<WindowId>>>> {
+    let render_app = app.get_sub_app_mut(RenderApp).ok()?;
+    let released_surfaces = Arc::default();
+    render_app
+        .insert_resource(ReleasedWindowSurfaces(Arc::clone(&released_surfaces)))
+        .add_system_to_stage(RenderStage::Extract, release_closed_window_surfaces);
+
+    Some(released_surfaces)
+}
+
+/// Drops all window surfaces, so that the windows can be destroyed.
+pub(crate) fn release_all_surfaces(app: &mut App) {
+    if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+        if let Some(mut window_surfaces) = render_app.world.get_resource_mut::<WindowSurfaces>() {
+            *window_surfaces = WindowSurfaces::default();
+        }
+    }
+}
+
+fn release_closed_window_surfaces(
+    mut closed: Extract<EventReader<WindowClosed>>,
+    window_surfaces: Option<ResMut<WindowSurfaces>>,
+    released_surfaces: Res<ReleasedWindowSurfaces>,
+) {
+    let closed: Vec<_> = closed.iter().map(|event| event.id).collect();
+    if closed.is_empty() {
+        return;
+    }
+
+    // surfaces can't be removed individually, the remaining ones get recreated when preparing
+    if let Some(mut window_surfaces) = window_surfaces {
+        *window_surfaces = WindowSurfaces::default();
+    }
+
+    released_surfaces.0.lock().unwrap().extend(closed);
+}
@@ -0,0 +1,182 @@
+use glfw_bindgen::*;
+use std::{ffi::c_int, time::Duration};
+
+/// Settings for [`GlfwPlugin`](crate::GlfwPlugin).
+///
+/// Insert this resource before adding the plugin. The init hints are applied before GLFW is
+/// initialized, changing them afterwards has no effect.
+#[derive(Debug, Clone)]
+pub struct GlfwSettings {
+    /// Platform (window system) GLFW should use.
+    pub platform: GlfwPlatform,
+    /// Also expose joystick hats as buttons.
+    pub joystick_hat_buttons: bool,
+    /// Rendering backend used by ANGLE, if GLFW was built with it.
+    pub angle_platform_type: GlfwAnglePlatformType,
+    /// macOS: change the working directory to the bundle's `Contents/Resources`.
+    pub cocoa_chdir_resources: bool,
+    /// macOS: create a basic menu bar.
+    pub cocoa_menubar: bool,
+    /// X11: prefer `VK_KHR_xcb_surface` over `VK_KHR_xlib_surface`.
+    pub x11_xcb_vulkan_surface: bool,
+    /// What to do if the primary window can't be created.
+    pub primary_window_fallback: PrimaryWindowFallback,
+    /// Update mode while any window is focused.
+    pub focused_mode: GlfwUpdateMode,
+    /// Update mode while no window is focused.
+    pub unfocused_mode: GlfwUpdateMode,
+    /// Frame rate limit while any window is focused.
+    pub focused_frame_rate_limit: Option<f64>,
+    /// Frame rate limit while no window is focused.
+    pub unfocused_frame_rate_limit: Option<f64>,
+    /// Frame rate limit while all windows are minimized, takes precedence over the others.
+    pub minimized_frame_rate_limit: Option<f64>,
+    /// Don't update the app at all while all windows are minimized.
+    pub pause_when_minimized: bool,
+    /// Update the app from the window refresh callback, which keeps windows responsive while
+    /// GLFW blocks in event processing, e.g. while resizing a window on Windows.
+    ///
+    /// Windows created or closed during such an update are only handled after event processing.
+    /// Disabled by default, as some platforms also refresh windows outside of live resizes.
+    pub update_on_refresh: bool,
+    /// Report the Caps Lock and Num Lock state in [`GlfwModifiers`](crate::GlfwModifiers).
+    pub lock_key_mods: bool,
+}
+
+impl Default for GlfwSettings {
+    fn default() -> Self {
+        GlfwSettings {
+            platform: GlfwPlatform::Any,
+            joystick_hat_buttons: true,
+            angle_platform_type: GlfwAnglePlatformType::None,
+            cocoa_chdir_resources: true,
+            cocoa_menubar: true,
+            x11_xcb_vulkan_surface: true,
+            primary_window_fallback: PrimaryWindowFallback::Panic,
+            focused_mode: GlfwUpdateMode::Continuous,
+            unfocused_mode: GlfwUpdateMode::Continuous,
+            focused_frame_rate_limit: None,
+            unfocused_frame_rate_limit: None,
+            minimized_frame_rate_limit: None,
+            pause_when_minimized: false,
+            update_on_refresh: false,
+            lock_key_mods: false,
+        }
+    }
+}
+
+impl GlfwSettings {
+    /// Settings for running without a display server, e.g. in CI.
+    ///
+    /// Windows are backed by GLFW's null platform and have no native handle,
+    /// so the app must not add Bevy's renderer.
+    pub fn headless() -> Self {
+        GlfwSettings {
+            platform: GlfwPlatform::Null,
+            ..Default::default()
+        }
+    }
+
+    /// Settings for applications that only need to update in response to input.
+    pub fn desktop_app() -> Self {
+        GlfwSettings {
+            focused_mode: GlfwUpdateMode::Reactive {
+                max_wait: Duration::from_secs(5),
+            },
+            unfocused_mode: GlfwUpdateMode::ReactiveLowPower {
+                max_wait: Duration::from_secs(60),
+            },
+            ..Default::default()
+        }
+    }
+
+    pub(crate) unsafe fn apply_init_hints(&self) {
+        glfwInitHint(GLFW_PLATFORM as _, self.platform.to_glfw());
+        glfwInitHint(
+            GLFW_JOYSTICK_HAT_BUTTONS as _,
+            self.joystick_hat_buttons as _,
+        );
+        glfwInitHint(
+            GLFW_ANGLE_PLATFORM_TYPE as _,
+            self.angle_platform_type.to_glfw(),
+        );
+        glfwInitHint(
+            GLFW_COCOA_CHDIR_RESOURCES as _,
+            self.cocoa_chdir_resources as _,
+        );
+        glfwInitHint(GLFW_COCOA_MENUBAR as _, self.cocoa_menubar as _);
+        glfwInitHint(
+            GLFW_X11_XCB_VULKAN_SURFACE as _,
+            self.x11_xcb_vulkan_surface as _,
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlfwUpdateMode {
+    /// Update as fast as possible.
+    Continuous,
+    /// Update when an event is received, a redraw is requested or `max_wait` has elapsed.
+    Reactive { max_wait: Duration },
+    /// Like [`GlfwUpdateMode::Reactive`], but cursor movement alone doesn't cause an update.
+    ReactiveLowPower { max_wait: Duration },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlfwPlatform {
+    /// Let GLFW pick the first supported platform.
+    Any,
+    Win32,
+    Cocoa,
+    Wayland,
+    X11,
+    /// No window system, windows are never shown.
+    Null,
+}
+
+impl GlfwPlatform {
+    fn to_glfw(self) -> c_int {
+        (match self {
+            GlfwPlatform::Any => GLFW_ANY_PLATFORM,
+            GlfwPlatform::Win32 => GLFW_PLATFORM_WIN32,
+            GlfwPlatform::Cocoa => GLFW_PLATFORM_COCOA,
+            GlfwPlatform::Wayland => GLFW_PLATFORM_WAYLAND,
+            GlfwPlatform::X11 => GLFW_PLATFORM_X11,
+            GlfwPlatform::Null => GLFW_PLATFORM_NULL,
+        }) as _
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlfwAnglePlatformType {
+    None,
+    OpenGl,
+    OpenGlEs,
+    D3D9,
+    D3D11,
+    Vulkan,
+    Metal,
+}
+
+impl GlfwAnglePlatformType {
+    fn to_glfw(self) -> c_int {
+        (match self {
+            GlfwAnglePlatformType::None => GLFW_ANGLE_PLATFORM_TYPE_NONE,
+            GlfwAnglePlatformType::OpenGl => GLFW_ANGLE_PLATFORM_TYPE_OPENGL,
+            GlfwAnglePlatformType::OpenGlEs => GLFW_ANGLE_PLATFORM_TYPE_OPENGLES,
+            GlfwAnglePlatformType::D3D9 => GLFW_ANGLE_PLATFORM_TYPE_D3D9,
+            GlfwAnglePlatformType::D3D11 => GLFW_ANGLE_PLATFORM_TYPE_D3D11,
+            GlfwAnglePlatformType::Vulkan => GLFW_ANGLE_PLATFORM_TYPE_VULKAN,
+            GlfwAnglePlatformType::Metal => GLFW_ANGLE_PLATFORM_TYPE_METAL,
+        }) as _
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimaryWindowFallback {
+    Panic,
+    /// Log the error and exit the process with a non-zero exit code.
+    Exit,
+    /// Try again as a plain, non-transparent windowed window, panic if that fails too.
+    RetryWithFewerHints,
+}
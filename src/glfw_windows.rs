@@ -1,12 +1,19 @@
 mod callbacks;
 
 use self::callbacks::{CallbackMetadata, GlfwEvent};
+use crate::{
+    glfw_errors::{GlfwError, WindowErrorScope},
+    glfw_input::{update_modifiers, GlfwKeyboardInput},
+    glfw_monitors::connected_monitors,
+    GlfwSettings,
+};
 use bevy::{
-    ecs::world::WorldCell,
+    ecs::{system::SystemParam, world::WorldCell},
     input::{
         keyboard::KeyboardInput,
-        mouse::{MouseButtonInput, MouseWheel},
+        mouse::{MouseButtonInput, MouseMotion, MouseWheel},
     },
+    math::DVec2,
     prelude::*,
     utils::HashMap,
     window::{
@@ -15,27 +22,48 @@ use bevy::{
         WindowScaleFactorChanged,
     },
 };
-use core::slice;
 use glfw_bindgen::*;
 use raw_window_handle::RawWindowHandle;
 use std::{
-    ffi::{c_int, CString},
+    error::Error,
+    ffi::{c_int, CStr, CString, NulError},
+    fmt,
+    marker::PhantomData,
     ptr,
+    sync::{Arc, Mutex},
 };
 
+/// Restricts a system to the main thread, the only one GLFW may be called from.
+pub(crate) type MainThread<'w> = NonSend<'w, GlfwWindows>;
+
+/// Non-send resource holding the GLFW state of every window.
 #[derive(Default)]
 pub struct GlfwWindows {
     pub windows: HashMap<WindowId, GlfwWindow>,
-    pub cursors: GlfwStandardCursors,
+    /// Hidden windows waiting to be destroyed after the renderer released their surface.
+    pub(crate) closed_windows: HashMap<WindowId, GlfwWindow>,
+    /// Windows whose surface was released, `None` if there is no renderer.
+    pub(crate) released_surfaces: Option<Arc<Mutex<Vec<WindowId>>>>,
+    pub(crate) cursors: GlfwStandardCursors,
+}
+
+impl Drop for GlfwWindows {
+    fn drop(&mut self) {
+        // destroy the windows before the cursors they may use
+        self.windows.clear();
+        self.closed_windows.clear();
+    }
 }
 
 impl GlfwWindows {
-    pub fn create_window(
+    pub(crate) fn create_window(
         &mut self,
         window_id: WindowId,
         window_descriptor: &WindowDescriptor,
-    ) -> Window {
-        let window = unsafe { GlfwWindow::new(window_id, window_descriptor) };
+        settings: &GlfwSettings,
+    ) -> Result<Window, WindowCreationError> {
+        let _error_scope = WindowErrorScope::new(window_id);
+        let window = unsafe { GlfwWindow::new(window_id, window_descriptor, settings)? };
         let bevy_window = Window::new(
             window_id,
             window_descriptor,
@@ -47,7 +75,126 @@ impl GlfwWindows {
         );
 
         self.windows.insert(window_id, window);
-        bevy_window
+        Ok(bevy_window)
+    }
+
+    pub(crate) unsafe fn close_window(&mut self, window_id: WindowId) {
+        let mut window = self.windows.remove(&window_id).unwrap();
+        if self.released_surfaces.is_none() {
+            // no renderer, but glfwDestroyWindow must not be called from a refresh callback update
+            glfwHideWindow(window.window);
+            self.closed_windows.insert(window_id, window);
+            return;
+        }
+
+        // glfwHideWindow only works on windowed windows
+        window.set_window_mode(WindowMode::Windowed, UVec2::default());
+        // glfwDestroyWindow may cause vkDestroySwapchain to deadlock, wait for the surface release
+        glfwHideWindow(window.window);
+        self.closed_windows.insert(window_id, window);
+    }
+
+    pub(crate) unsafe fn destroy_released_windows(&mut self) {
+        let Some(released_surfaces) = &self.released_surfaces else {
+            self.closed_windows.clear();
+            return;
+        };
+
+        for window_id in released_surfaces.lock().unwrap().drain(..) {
+            self.closed_windows.remove(&window_id);
+        }
+    }
+
+    pub(crate) unsafe fn all_minimized(&self) -> bool {
+        !self.windows.is_empty()
+            && self.windows.values().all(|window| {
+                glfwGetWindowAttrib(window.window, GLFW_ICONIFIED as _) == GLFW_TRUE as c_int
+            })
+    }
+
+    pub(crate) unsafe fn close_requested(&self) -> bool {
+        self.windows.values().any(|window| {
+            let callback_metadata =
+                glfwGetWindowUserPointer(window.window).cast::<CallbackMetadata>();
+            (*callback_metadata)
+                .events
+                .iter()
+                .any(|event| matches!(event, GlfwEvent::WindowClose))
+        })
+    }
+
+    // cursor motion doesn't count, it would wake up low power modes constantly
+    pub(crate) unsafe fn has_pending_events(&self) -> bool {
+        self.windows.values().any(|window| {
+            let callback_metadata =
+                glfwGetWindowUserPointer(window.window).cast::<CallbackMetadata>();
+            (*callback_metadata)
+                .events
+                .iter()
+                .any(|event| !matches!(event, GlfwEvent::CursorPos(_)))
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum WindowCreationError {
+    ScaleFactorOverride,
+    InvalidTitle(NulError),
+    Glfw {
+        error: GlfwError,
+        description: String,
+    },
+}
+
+impl fmt::Display for WindowCreationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowCreationError::ScaleFactorOverride => {
+                write!(f, "Overriding scale factor not supported")
+            }
+            WindowCreationError::InvalidTitle(err) => write!(f, "Invalid window title: {err}"),
+            WindowCreationError::Glfw { error, description } => {
+                write!(f, "GLFW error {error}: {description}")
+            }
+        }
+    }
+}
+
+impl Error for WindowCreationError {}
+
+/// Sent instead of [`WindowCreated`](bevy::window::WindowCreated) if a window couldn't be created.
+#[derive(Debug, Clone)]
+pub struct WindowCreationFailed {
+    pub id: WindowId,
+    pub reason: WindowCreationError,
+}
+
+/// Sent when a window is minimized or restored.
+#[derive(Debug, Clone)]
+pub struct WindowMinimized {
+    pub id: WindowId,
+    pub minimized: bool,
+}
+
+/// Sent when a window is maximized or restored.
+#[derive(Debug, Clone)]
+pub struct WindowMaximized {
+    pub id: WindowId,
+    pub maximized: bool,
+}
+
+/// Read-only access to window state that Bevy's [`Window`] doesn't track.
+#[derive(SystemParam)]
+pub struct GlfwWindowStates<'w, 's> {
+    glfw_windows: NonSend<'w, GlfwWindows>,
+    #[system_param(ignore)]
+    _marker: PhantomData<&'s ()>,
+}
+
+impl<'w, 's> GlfwWindowStates<'w, 's> {
+    /// `false` for unknown windows.
+    pub fn is_maximized(&self, id: WindowId) -> bool {
+        matches!(self.glfw_windows.windows.get(&id), Some(window) if window.maximized)
     }
 }
 
@@ -60,6 +207,9 @@ pub struct GlfwWindow {
     pub scale_factor: f64,
     pub cursor_visible: bool,
     pub cursor_locked: bool,
+    pub maximized: bool,
+    /// Cursor position of the last cursor event, in screen coordinates with a top-left origin.
+    pub last_cursor_pos: Option<DVec2>,
 }
 
 impl GlfwWindow {
@@ -82,18 +232,19 @@ impl GlfwWindow {
             canvas: _,
             fit_canvas_to_parent: _,
         }: &WindowDescriptor,
-    ) -> GlfwWindow {
+        settings: &GlfwSettings,
+    ) -> Result<GlfwWindow, WindowCreationError> {
+        if scale_factor_override.is_some() {
+            return Err(WindowCreationError::ScaleFactorOverride);
+        }
+
+        let title = CString::new(title.as_str()).map_err(WindowCreationError::InvalidTitle)?;
+
         glfwWindowHint(GLFW_CLIENT_API as _, GLFW_NO_API as _);
         glfwWindowHint(GLFW_RESIZABLE as _, *resizable as _);
         glfwWindowHint(GLFW_DECORATED as _, *decorations as _);
         glfwWindowHint(GLFW_TRANSPARENT_FRAMEBUFFER as _, *transparent as _);
 
-        assert_eq!(
-            *scale_factor_override, None,
-            "Overriding scale factor not supported"
-        );
-
-        let title = CString::new(title.as_str()).expect("Invalid window title");
         let window = glfwCreateWindow(
             *width as c_int,
             *height as c_int,
@@ -101,9 +252,20 @@ impl GlfwWindow {
             ptr::null_mut(),
             ptr::null_mut(),
         );
-        assert!(!window.is_null(), "Failed to create window");
+        if window.is_null() {
+            let mut description = ptr::null();
+            let error = GlfwError::from_code(glfwGetError(&mut description));
+            let description = if description.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(description).to_string_lossy().into_owned()
+            };
+
+            return Err(WindowCreationError::Glfw { error, description });
+        }
 
         unsafe { CallbackMetadata::register(window, window_id) }
+        glfwSetInputMode(window, GLFW_LOCK_KEY_MODS as _, settings.lock_key_mods as _);
 
         let mut xpos = 0;
         let mut ypos = 0;
@@ -127,6 +289,8 @@ impl GlfwWindow {
             scale_factor: xscale as _,
             cursor_visible: *cursor_visible,
             cursor_locked: *cursor_locked,
+            maximized: glfwGetWindowAttrib(window, GLFW_MAXIMIZED as _) == GLFW_TRUE as c_int,
+            last_cursor_pos: None,
         };
 
         match position {
@@ -138,7 +302,7 @@ impl GlfwWindow {
         window.set_resize_constraints(*resize_constraints);
         window.update_cursor_mode();
         window.set_window_mode(*mode, window.size);
-        window
+        Ok(window)
     }
 
     unsafe fn current_monitor(&self) -> *mut GLFWmonitor {
@@ -174,7 +338,7 @@ impl GlfwWindow {
             .unwrap()
     }
 
-    pub unsafe fn set_window_mode(&mut self, mode: WindowMode, resolution: UVec2) {
+    pub(crate) unsafe fn set_window_mode(&mut self, mode: WindowMode, resolution: UVec2) {
         let set_windowed = matches!(mode, WindowMode::Windowed);
         let currently_windowed = glfwGetWindowMonitor(self.window).is_null();
         match (currently_windowed, set_windowed) {
@@ -221,17 +385,17 @@ impl GlfwWindow {
         }
     }
 
-    pub unsafe fn set_pos(&mut self, pos: IVec2) {
+    pub(crate) unsafe fn set_pos(&mut self, pos: IVec2) {
         self.pos = pos;
         glfwSetWindowPos(self.window, pos.x, pos.y);
     }
 
-    pub unsafe fn set_size(&mut self, size: UVec2) {
+    pub(crate) unsafe fn set_size(&mut self, size: UVec2) {
         self.size = size;
         glfwSetWindowSize(self.window, size.x as _, size.y as _);
     }
 
-    pub unsafe fn center_to(&mut self, monitor_selection: MonitorSelection) {
+    pub(crate) unsafe fn center_to(&mut self, monitor_selection: MonitorSelection) {
         let monitor = match monitor_selection {
             MonitorSelection::Current => self.current_monitor(),
             MonitorSelection::Primary => monitors()[0],
@@ -253,7 +417,7 @@ impl GlfwWindow {
         ));
     }
 
-    pub unsafe fn set_resize_constraints(&mut self, constraints: WindowResizeConstraints) {
+    pub(crate) unsafe fn set_resize_constraints(&mut self, constraints: WindowResizeConstraints) {
         let conv = |dim: f32| {
             if dim.is_normal() {
                 dim as c_int
@@ -271,7 +435,10 @@ impl GlfwWindow {
         );
     }
 
-    pub unsafe fn update_cursor_mode(&self) {
+    pub(crate) unsafe fn update_cursor_mode(&mut self) {
+        // switching between the virtual and the real cursor position makes it jump
+        self.last_cursor_pos = None;
+
         glfwSetInputMode(
             self.window,
             GLFW_CURSOR as _,
@@ -294,6 +461,11 @@ impl GlfwWindow {
     }
 
     unsafe fn raw_window_handle(&self) -> RawWindowHandle {
+        // the null platform has no native window, hand out an invalid handle
+        if glfwGetPlatform() == GLFW_PLATFORM_NULL as c_int {
+            return RawWindowHandle::Web(raw_window_handle::WebHandle::empty());
+        }
+
         #[cfg(target_family = "windows")]
         'windows: {
             let hwnd = glfwGetWin32Window(self.window);
@@ -348,7 +520,7 @@ impl GlfwWindow {
         panic!("No window backend found")
     }
 
-    pub unsafe fn handle_events(&mut self, world: &WorldCell, bevy_window: &mut Window) {
+    pub(crate) unsafe fn handle_events(&mut self, world: &WorldCell, bevy_window: &mut Window) {
         let callback_metadata = glfwGetWindowUserPointer(self.window).cast::<CallbackMetadata>();
         let mut send_size = false;
         for event in (*callback_metadata).events.drain(..) {
@@ -367,6 +539,25 @@ impl GlfwWindow {
                             focused,
                         });
                 }
+                GlfwEvent::WindowIconified(minimized) => {
+                    world
+                        .resource_mut::<Events<WindowMinimized>>()
+                        .send(WindowMinimized {
+                            id: (*callback_metadata).window_id,
+                            minimized,
+                        });
+                }
+                GlfwEvent::WindowMaximized(maximized) => {
+                    self.maximized = maximized;
+                    world
+                        .resource_mut::<Events<WindowMaximized>>()
+                        .send(WindowMaximized {
+                            id: (*callback_metadata).window_id,
+                            maximized,
+                        });
+                }
+                // only wakes up reactive update modes
+                GlfwEvent::WindowRefresh => (),
                 GlfwEvent::WindowContentScale(scale_factor) => {
                     self.scale_factor = scale_factor;
                     world
@@ -422,6 +613,7 @@ impl GlfwWindow {
                                 id: (*callback_metadata).window_id,
                             });
                     } else {
+                        self.last_cursor_pos = None;
                         bevy_window.update_cursor_physical_position_from_backend(None);
                         world.resource_mut::<Events<CursorLeft>>().send(CursorLeft {
                             id: (*callback_metadata).window_id,
@@ -429,6 +621,15 @@ impl GlfwWindow {
                     }
                 }
                 GlfwEvent::CursorPos(mut pos) => {
+                    // raw if the cursor is locked and raw mouse motion is supported
+                    if let Some(last_pos) = self.last_cursor_pos.replace(pos) {
+                        world
+                            .resource_mut::<Events<MouseMotion>>()
+                            .send(MouseMotion {
+                                delta: (pos - last_pos).as_vec2(),
+                            });
+                    }
+
                     pos.y = self.size.y as f64 - pos.y; // convert top-left -> bottom-left origin
                     let physical = pos * self.scale_factor;
                     bevy_window.update_cursor_physical_position_from_backend(Some(physical));
@@ -439,6 +640,9 @@ impl GlfwWindow {
                             position: pos.as_vec2(),
                         });
                 }
+                GlfwEvent::Modifiers(modifiers) => {
+                    update_modifiers(world, (*callback_metadata).window_id, modifiers);
+                }
                 GlfwEvent::MouseButton(event) => {
                     world.resource_mut::<Events<MouseButtonInput>>().send(event);
                 }
@@ -450,7 +654,14 @@ impl GlfwWindow {
                             char,
                         });
                 }
-                GlfwEvent::Key(event) => {
+                GlfwEvent::Key(event, repeat) => {
+                    world
+                        .resource_mut::<Events<GlfwKeyboardInput>>()
+                        .send(GlfwKeyboardInput {
+                            id: (*callback_metadata).window_id,
+                            input: event.clone(),
+                            repeat,
+                        });
                     world.resource_mut::<Events<KeyboardInput>>().send(event);
                 }
             }
@@ -468,7 +679,20 @@ impl GlfwWindow {
     }
 }
 
-#[derive(Clone)]
+impl Drop for GlfwWindow {
+    fn drop(&mut self) {
+        unsafe {
+            let callback_metadata =
+                glfwGetWindowUserPointer(self.window).cast::<CallbackMetadata>();
+            glfwDestroyWindow(self.window);
+            // null if GLFW was already terminated
+            if !callback_metadata.is_null() {
+                drop(Box::from_raw(callback_metadata));
+            }
+        }
+    }
+}
+
 pub struct GlfwStandardCursors {
     pub arrow: *mut GLFWcursor,
     pub ibeam: *mut GLFWcursor,
@@ -501,12 +725,25 @@ impl Default for GlfwStandardCursors {
     }
 }
 
-// first monitor is the primary monitor
-// SAFETY: only valid until glfw is terminated or the monitor config changes
+impl Drop for GlfwStandardCursors {
+    fn drop(&mut self) {
+        unsafe {
+            glfwDestroyCursor(self.arrow);
+            glfwDestroyCursor(self.ibeam);
+            glfwDestroyCursor(self.crosshair);
+            glfwDestroyCursor(self.pointing_hand);
+            glfwDestroyCursor(self.resize_ew);
+            glfwDestroyCursor(self.resize_ns);
+            glfwDestroyCursor(self.resize_nwse);
+            glfwDestroyCursor(self.resize_nesw);
+            glfwDestroyCursor(self.resize_all);
+            glfwDestroyCursor(self.not_allowed);
+        }
+    }
+}
+
 unsafe fn monitors() -> &'static [*mut GLFWmonitor] {
-    let mut monitor_count = 0;
-    let monitors_ptr = glfwGetMonitors(&mut monitor_count);
-    let monitors = slice::from_raw_parts(monitors_ptr, monitor_count as _);
+    let monitors = connected_monitors();
     assert!(!monitors.is_empty(), "No monitors found");
     monitors
 }
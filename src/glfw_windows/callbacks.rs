@@ -1,3 +1,7 @@
+use crate::{
+    glfw_input::GlfwModifiers,
+    keys::{glfw_key_to_keycode, glfw_mouse_button_to_mouse_button},
+};
 use bevy::{
     input::{keyboard::KeyboardInput, mouse::MouseButtonInput, ButtonState},
     math::DVec2,
@@ -15,6 +19,8 @@ use std::{
 pub struct CallbackMetadata {
     pub window_id: WindowId,
     pub events: Vec<GlfwEvent>,
+    /// Lock modifiers that were active when their key was last pressed.
+    pub lock_mods_before_press: c_int,
 }
 
 impl CallbackMetadata {
@@ -27,6 +33,9 @@ impl CallbackMetadata {
         glfwSetWindowUserPointer(window, Box::into_raw(Box::new(callback_metadata)).cast());
         glfwSetWindowCloseCallback(window, Some(windowclose));
         glfwSetWindowFocusCallback(window, Some(windowfocus));
+        glfwSetWindowIconifyCallback(window, Some(windowiconify));
+        glfwSetWindowMaximizeCallback(window, Some(windowmaximize));
+        glfwSetWindowRefreshCallback(window, Some(windowrefresh));
         glfwSetWindowContentScaleCallback(window, Some(windowcontentscale));
         glfwSetWindowPosCallback(window, Some(windowpos));
         glfwSetWindowSizeCallback(window, Some(windowsize));
@@ -45,6 +54,9 @@ impl CallbackMetadata {
 pub enum GlfwEvent {
     WindowClose,
     WindowFocused(bool),
+    WindowIconified(bool),
+    WindowMaximized(bool),
+    WindowRefresh,
     WindowContentScale(f64),
     WindowPos(IVec2),
     WindowSize(UVec2),
@@ -53,9 +65,12 @@ pub enum GlfwEvent {
     Scroll(Vec2),
     CursorEnter(bool),
     CursorPos(DVec2),
+    /// Precedes the key and mouse button events it was reported with.
+    Modifiers(GlfwModifiers),
     MouseButton(MouseButtonInput),
     Char(char),
-    Key(KeyboardInput),
+    /// Whether the key press is a repeat.
+    Key(KeyboardInput, bool),
 }
 
 pub unsafe extern "C" fn windowclose(window: *mut GLFWwindow) {
@@ -70,6 +85,27 @@ pub unsafe extern "C" fn windowfocus(window: *mut GLFWwindow, focused: c_int) {
         .push(GlfwEvent::WindowFocused(focused == GLFW_TRUE as c_int));
 }
 
+pub unsafe extern "C" fn windowiconify(window: *mut GLFWwindow, iconified: c_int) {
+    (*glfwGetWindowUserPointer(window).cast::<CallbackMetadata>())
+        .events
+        .push(GlfwEvent::WindowIconified(iconified == GLFW_TRUE as c_int));
+}
+
+pub unsafe extern "C" fn windowmaximize(window: *mut GLFWwindow, maximized: c_int) {
+    (*glfwGetWindowUserPointer(window).cast::<CallbackMetadata>())
+        .events
+        .push(GlfwEvent::WindowMaximized(maximized == GLFW_TRUE as c_int));
+}
+
+pub unsafe extern "C" fn windowrefresh(window: *mut GLFWwindow) {
+    (*glfwGetWindowUserPointer(window).cast::<CallbackMetadata>())
+        .events
+        .push(GlfwEvent::WindowRefresh);
+
+    // event processing may block during live resizes, update from within
+    crate::glfw_app_runner::update_from_refresh();
+}
+
 pub unsafe extern "C" fn windowcontentscale(window: *mut GLFWwindow, xscale: f32, yscale: f32) {
     assert_eq!(xscale, yscale);
 
@@ -139,17 +175,15 @@ pub unsafe extern "C" fn mousebutton(
     window: *mut GLFWwindow,
     button: c_int,
     action: c_int,
-    _mods: c_int,
+    mods: c_int,
 ) {
+    (*glfwGetWindowUserPointer(window).cast::<CallbackMetadata>())
+        .events
+        .push(GlfwEvent::Modifiers(GlfwModifiers::from_glfw(mods)));
     (*glfwGetWindowUserPointer(window).cast::<CallbackMetadata>())
         .events
         .push(GlfwEvent::MouseButton(MouseButtonInput {
-            button: match button + 1 {
-                1 => MouseButton::Left,
-                2 => MouseButton::Right,
-                3 => MouseButton::Middle,
-                other => MouseButton::Other(other as u16),
-            },
+            button: glfw_mouse_button_to_mouse_button(button),
             state: match action as u32 {
                 GLFW_PRESS => ButtonState::Pressed,
                 GLFW_RELEASE => ButtonState::Released,
@@ -171,134 +205,169 @@ pub unsafe extern "C" fn key(
     key: c_int,
     scancode: c_int,
     action: c_int,
-    _mods: c_int,
+    mods: c_int,
 ) {
+    // the key states are updated before the callback, the other side may still be held
+    let pressed = |key: c_int| glfwGetKey(window, key) == GLFW_PRESS as c_int;
+    let mods = modifier_key_mods(key, mods, pressed);
+    let mods = if reports_stale_lock_mods(window) {
+        let callback_metadata = &mut *glfwGetWindowUserPointer(window).cast::<CallbackMetadata>();
+        lock_key_mods(
+            key,
+            action,
+            mods,
+            &mut callback_metadata.lock_mods_before_press,
+        )
+    } else {
+        mods
+    };
     (*glfwGetWindowUserPointer(window).cast::<CallbackMetadata>())
         .events
-        .push(GlfwEvent::Key(KeyboardInput {
-            scan_code: scancode as _,
-            key_code: match key as u32 {
-                GLFW_KEY_SPACE => Some(KeyCode::Space),
-                GLFW_KEY_APOSTROPHE => Some(KeyCode::Apostrophe),
-                GLFW_KEY_COMMA => Some(KeyCode::Comma),
-                GLFW_KEY_MINUS => Some(KeyCode::Minus),
-                GLFW_KEY_PERIOD => Some(KeyCode::Period),
-                GLFW_KEY_SLASH => Some(KeyCode::Slash),
-                GLFW_KEY_0 => Some(KeyCode::Key0),
-                GLFW_KEY_1 => Some(KeyCode::Key1),
-                GLFW_KEY_2 => Some(KeyCode::Key2),
-                GLFW_KEY_3 => Some(KeyCode::Key3),
-                GLFW_KEY_4 => Some(KeyCode::Key4),
-                GLFW_KEY_5 => Some(KeyCode::Key5),
-                GLFW_KEY_6 => Some(KeyCode::Key6),
-                GLFW_KEY_7 => Some(KeyCode::Key7),
-                GLFW_KEY_8 => Some(KeyCode::Key8),
-                GLFW_KEY_9 => Some(KeyCode::Key9),
-                GLFW_KEY_SEMICOLON => Some(KeyCode::Semicolon),
-                GLFW_KEY_EQUAL => Some(KeyCode::Equals),
-                GLFW_KEY_A => Some(KeyCode::A),
-                GLFW_KEY_B => Some(KeyCode::B),
-                GLFW_KEY_C => Some(KeyCode::C),
-                GLFW_KEY_D => Some(KeyCode::D),
-                GLFW_KEY_E => Some(KeyCode::E),
-                GLFW_KEY_F => Some(KeyCode::F),
-                GLFW_KEY_G => Some(KeyCode::G),
-                GLFW_KEY_H => Some(KeyCode::H),
-                GLFW_KEY_I => Some(KeyCode::I),
-                GLFW_KEY_J => Some(KeyCode::J),
-                GLFW_KEY_K => Some(KeyCode::K),
-                GLFW_KEY_L => Some(KeyCode::L),
-                GLFW_KEY_M => Some(KeyCode::M),
-                GLFW_KEY_N => Some(KeyCode::N),
-                GLFW_KEY_O => Some(KeyCode::O),
-                GLFW_KEY_P => Some(KeyCode::P),
-                GLFW_KEY_Q => Some(KeyCode::Q),
-                GLFW_KEY_R => Some(KeyCode::R),
-                GLFW_KEY_S => Some(KeyCode::S),
-                GLFW_KEY_T => Some(KeyCode::T),
-                GLFW_KEY_U => Some(KeyCode::U),
-                GLFW_KEY_V => Some(KeyCode::V),
-                GLFW_KEY_W => Some(KeyCode::W),
-                GLFW_KEY_X => Some(KeyCode::X),
-                GLFW_KEY_Y => Some(KeyCode::Y),
-                GLFW_KEY_Z => Some(KeyCode::Z),
-                GLFW_KEY_LEFT_BRACKET => Some(KeyCode::LBracket),
-                GLFW_KEY_BACKSLASH => Some(KeyCode::Backslash),
-                GLFW_KEY_RIGHT_BRACKET => Some(KeyCode::RBracket),
-                GLFW_KEY_GRAVE_ACCENT => Some(KeyCode::Grave),
-                GLFW_KEY_ESCAPE => Some(KeyCode::Escape),
-                GLFW_KEY_ENTER => Some(KeyCode::Return),
-                GLFW_KEY_TAB => Some(KeyCode::Tab),
-                GLFW_KEY_BACKSPACE => Some(KeyCode::Back),
-                GLFW_KEY_INSERT => Some(KeyCode::Insert),
-                GLFW_KEY_DELETE => Some(KeyCode::Delete),
-                GLFW_KEY_RIGHT => Some(KeyCode::Right),
-                GLFW_KEY_LEFT => Some(KeyCode::Left),
-                GLFW_KEY_DOWN => Some(KeyCode::Down),
-                GLFW_KEY_UP => Some(KeyCode::Up),
-                GLFW_KEY_PAGE_UP => Some(KeyCode::PageUp),
-                GLFW_KEY_PAGE_DOWN => Some(KeyCode::PageDown),
-                GLFW_KEY_HOME => Some(KeyCode::Home),
-                GLFW_KEY_END => Some(KeyCode::End),
-                GLFW_KEY_SCROLL_LOCK => Some(KeyCode::Scroll),
-                GLFW_KEY_NUM_LOCK => Some(KeyCode::Numlock),
-                GLFW_KEY_PRINT_SCREEN => Some(KeyCode::Snapshot),
-                GLFW_KEY_PAUSE => Some(KeyCode::Pause),
-                GLFW_KEY_F1 => Some(KeyCode::F1),
-                GLFW_KEY_F2 => Some(KeyCode::F2),
-                GLFW_KEY_F3 => Some(KeyCode::F3),
-                GLFW_KEY_F4 => Some(KeyCode::F4),
-                GLFW_KEY_F5 => Some(KeyCode::F5),
-                GLFW_KEY_F6 => Some(KeyCode::F6),
-                GLFW_KEY_F7 => Some(KeyCode::F7),
-                GLFW_KEY_F8 => Some(KeyCode::F8),
-                GLFW_KEY_F9 => Some(KeyCode::F9),
-                GLFW_KEY_F10 => Some(KeyCode::F10),
-                GLFW_KEY_F11 => Some(KeyCode::F11),
-                GLFW_KEY_F12 => Some(KeyCode::F12),
-                GLFW_KEY_F13 => Some(KeyCode::F13),
-                GLFW_KEY_F14 => Some(KeyCode::F14),
-                GLFW_KEY_F15 => Some(KeyCode::F15),
-                GLFW_KEY_F16 => Some(KeyCode::F16),
-                GLFW_KEY_F17 => Some(KeyCode::F17),
-                GLFW_KEY_F18 => Some(KeyCode::F18),
-                GLFW_KEY_F19 => Some(KeyCode::F19),
-                GLFW_KEY_F20 => Some(KeyCode::F20),
-                GLFW_KEY_F21 => Some(KeyCode::F21),
-                GLFW_KEY_F22 => Some(KeyCode::F22),
-                GLFW_KEY_F23 => Some(KeyCode::F23),
-                GLFW_KEY_F24 => Some(KeyCode::F24),
-                GLFW_KEY_KP_0 => Some(KeyCode::Numpad0),
-                GLFW_KEY_KP_1 => Some(KeyCode::Numpad1),
-                GLFW_KEY_KP_2 => Some(KeyCode::Numpad2),
-                GLFW_KEY_KP_3 => Some(KeyCode::Numpad3),
-                GLFW_KEY_KP_4 => Some(KeyCode::Numpad4),
-                GLFW_KEY_KP_5 => Some(KeyCode::Numpad5),
-                GLFW_KEY_KP_6 => Some(KeyCode::Numpad6),
-                GLFW_KEY_KP_7 => Some(KeyCode::Numpad7),
-                GLFW_KEY_KP_8 => Some(KeyCode::Numpad8),
-                GLFW_KEY_KP_9 => Some(KeyCode::Numpad9),
-                GLFW_KEY_KP_DECIMAL => Some(KeyCode::NumpadDecimal),
-                GLFW_KEY_KP_DIVIDE => Some(KeyCode::NumpadDivide),
-                GLFW_KEY_KP_MULTIPLY => Some(KeyCode::NumpadMultiply),
-                GLFW_KEY_KP_SUBTRACT => Some(KeyCode::NumpadSubtract),
-                GLFW_KEY_KP_ADD => Some(KeyCode::NumpadAdd),
-                GLFW_KEY_KP_ENTER => Some(KeyCode::NumpadEnter),
-                GLFW_KEY_KP_EQUAL => Some(KeyCode::NumpadEquals),
-                GLFW_KEY_LEFT_SHIFT => Some(KeyCode::LShift),
-                GLFW_KEY_LEFT_CONTROL => Some(KeyCode::LControl),
-                GLFW_KEY_LEFT_ALT => Some(KeyCode::LAlt),
-                GLFW_KEY_LEFT_SUPER => Some(KeyCode::LWin),
-                GLFW_KEY_RIGHT_SHIFT => Some(KeyCode::RShift),
-                GLFW_KEY_RIGHT_CONTROL => Some(KeyCode::RControl),
-                GLFW_KEY_RIGHT_ALT => Some(KeyCode::RAlt),
-                GLFW_KEY_RIGHT_SUPER => Some(KeyCode::RWin),
-                _ => None,
-            },
-            state: match action as u32 {
-                GLFW_PRESS | GLFW_REPEAT => ButtonState::Pressed,
-                GLFW_RELEASE => ButtonState::Released,
-                _ => unreachable!(),
+        .push(GlfwEvent::Modifiers(GlfwModifiers::from_glfw(mods)));
+    (*glfwGetWindowUserPointer(window).cast::<CallbackMetadata>())
+        .events
+        .push(GlfwEvent::Key(
+            KeyboardInput {
+                scan_code: scancode as _,
+                key_code: glfw_key_to_keycode(key),
+                state: match action as u32 {
+                    GLFW_PRESS | GLFW_REPEAT => ButtonState::Pressed,
+                    GLFW_RELEASE => ButtonState::Released,
+                    _ => unreachable!(),
+                },
             },
-        }));
+            action == GLFW_REPEAT as c_int,
+        ));
+}
+
+// X11 and Wayland report the modifiers from before the event, which is wrong for modifier keys
+fn modifier_key_mods(key: c_int, mods: c_int, pressed: impl Fn(c_int) -> bool) -> c_int {
+    let (modifier, left, right) = match key as u32 {
+        GLFW_KEY_LEFT_SHIFT | GLFW_KEY_RIGHT_SHIFT => {
+            (GLFW_MOD_SHIFT, GLFW_KEY_LEFT_SHIFT, GLFW_KEY_RIGHT_SHIFT)
+        }
+        GLFW_KEY_LEFT_CONTROL | GLFW_KEY_RIGHT_CONTROL => (
+            GLFW_MOD_CONTROL,
+            GLFW_KEY_LEFT_CONTROL,
+            GLFW_KEY_RIGHT_CONTROL,
+        ),
+        GLFW_KEY_LEFT_ALT | GLFW_KEY_RIGHT_ALT => {
+            (GLFW_MOD_ALT, GLFW_KEY_LEFT_ALT, GLFW_KEY_RIGHT_ALT)
+        }
+        GLFW_KEY_LEFT_SUPER | GLFW_KEY_RIGHT_SUPER => {
+            (GLFW_MOD_SUPER, GLFW_KEY_LEFT_SUPER, GLFW_KEY_RIGHT_SUPER)
+        }
+        _ => return mods,
+    };
+
+    if pressed(left as _) || pressed(right as _) {
+        mods | modifier as c_int
+    } else {
+        mods & !(modifier as c_int)
+    }
+}
+
+// X11 and Wayland lock the modifier on press, but only unlock it on the following release
+unsafe fn reports_stale_lock_mods(window: *mut GLFWwindow) -> bool {
+    glfwGetInputMode(window, GLFW_LOCK_KEY_MODS as _) == GLFW_TRUE as c_int
+        && matches!(
+            glfwGetPlatform() as u32,
+            GLFW_PLATFORM_X11 | GLFW_PLATFORM_WAYLAND
+        )
+}
+
+fn lock_key_mods(key: c_int, action: c_int, mods: c_int, before_press: &mut c_int) -> c_int {
+    let modifier = match key as u32 {
+        GLFW_KEY_CAPS_LOCK => GLFW_MOD_CAPS_LOCK as c_int,
+        GLFW_KEY_NUM_LOCK => GLFW_MOD_NUM_LOCK as c_int,
+        _ => return mods,
+    };
+
+    let locked = match action as u32 {
+        GLFW_PRESS => {
+            *before_press &= !modifier;
+            *before_press |= mods & modifier;
+            true
+        }
+        GLFW_RELEASE => *before_press & modifier == 0,
+        _ => return mods,
+    };
+
+    if locked {
+        mods | modifier
+    } else {
+        mods & !modifier
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIFT: c_int = GLFW_MOD_SHIFT as _;
+    const CONTROL: c_int = GLFW_MOD_CONTROL as _;
+    const CAPS_LOCK: c_int = GLFW_MOD_CAPS_LOCK as _;
+    const NUM_LOCK: c_int = GLFW_MOD_NUM_LOCK as _;
+
+    #[test]
+    fn modifier_keys() {
+        let left_shift = GLFW_KEY_LEFT_SHIFT as c_int;
+        let right_shift = GLFW_KEY_RIGHT_SHIFT as c_int;
+        let only = |held: c_int| move |key: c_int| key == held;
+
+        // pressing reports the modifier the OS didn't include yet
+        assert_eq!(modifier_key_mods(left_shift, 0, only(left_shift)), SHIFT);
+        // releasing one side keeps it while the other side is held
+        assert_eq!(
+            modifier_key_mods(left_shift, SHIFT, only(right_shift)),
+            SHIFT
+        );
+        assert_eq!(
+            modifier_key_mods(left_shift, SHIFT | CONTROL, |_| false),
+            CONTROL
+        );
+        // other keys report the modifiers unchanged
+        let key_a = GLFW_KEY_A as c_int;
+        assert_eq!(modifier_key_mods(key_a, SHIFT, |_| false), SHIFT);
+    }
+
+    #[test]
+    fn lock_keys() {
+        let caps_lock = GLFW_KEY_CAPS_LOCK as c_int;
+        let num_lock = GLFW_KEY_NUM_LOCK as c_int;
+        let (press, release) = (GLFW_PRESS as c_int, GLFW_RELEASE as c_int);
+        let mut before_press = 0;
+
+        // locking reports the lock on press and keeps it on release
+        assert_eq!(
+            lock_key_mods(caps_lock, press, 0, &mut before_press),
+            CAPS_LOCK
+        );
+        assert_eq!(
+            lock_key_mods(caps_lock, release, 0, &mut before_press),
+            CAPS_LOCK
+        );
+        // other lock keys don't affect it
+        assert_eq!(
+            lock_key_mods(num_lock, press, CAPS_LOCK, &mut before_press),
+            CAPS_LOCK | NUM_LOCK
+        );
+        // unlocking only happens on the following release
+        assert_eq!(
+            lock_key_mods(caps_lock, press, CAPS_LOCK, &mut before_press),
+            CAPS_LOCK
+        );
+        assert_eq!(
+            lock_key_mods(caps_lock, release, CAPS_LOCK, &mut before_press),
+            0
+        );
+        // repeats and other keys report the modifiers unchanged
+        let repeat = GLFW_REPEAT as c_int;
+        assert_eq!(
+            lock_key_mods(num_lock, repeat, NUM_LOCK, &mut before_press),
+            NUM_LOCK
+        );
+        let key_a = GLFW_KEY_A as c_int;
+        assert_eq!(lock_key_mods(key_a, press, SHIFT, &mut before_press), SHIFT);
+    }
 }
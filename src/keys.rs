@@ -0,0 +1,412 @@
+//! Conversions between GLFW key and mouse button constants and their Bevy equivalents.
+
+use bevy::prelude::*;
+use glfw_bindgen::*;
+use std::ffi::c_int;
+
+macro_rules! key_mapping {
+    ($($glfw_key:ident <=> $key_code:ident,)*) => {
+        /// Maps a GLFW key to the closest [`KeyCode`], `None` for `GLFW_KEY_F25` and unknown keys.
+        pub fn glfw_key_to_keycode(key: c_int) -> Option<KeyCode> {
+            match key as u32 {
+                $($glfw_key => Some(KeyCode::$key_code),)*
+                // non-US backslash on Windows and Wayland
+                GLFW_KEY_WORLD_2 => Some(KeyCode::Oem102),
+                _ => None,
+            }
+        }
+
+        /// Maps a [`KeyCode`] to its GLFW key, `None` if GLFW has no such key.
+        ///
+        /// [`KeyCode::Oem102`] maps to `GLFW_KEY_WORLD_1`.
+        pub fn keycode_to_glfw_key(key_code: KeyCode) -> Option<c_int> {
+            match key_code {
+                $(KeyCode::$key_code => Some($glfw_key as c_int),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+key_mapping! {
+    GLFW_KEY_SPACE <=> Space,
+    GLFW_KEY_APOSTROPHE <=> Apostrophe,
+    GLFW_KEY_COMMA <=> Comma,
+    GLFW_KEY_MINUS <=> Minus,
+    GLFW_KEY_PERIOD <=> Period,
+    GLFW_KEY_SLASH <=> Slash,
+    GLFW_KEY_0 <=> Key0,
+    GLFW_KEY_1 <=> Key1,
+    GLFW_KEY_2 <=> Key2,
+    GLFW_KEY_3 <=> Key3,
+    GLFW_KEY_4 <=> Key4,
+    GLFW_KEY_5 <=> Key5,
+    GLFW_KEY_6 <=> Key6,
+    GLFW_KEY_7 <=> Key7,
+    GLFW_KEY_8 <=> Key8,
+    GLFW_KEY_9 <=> Key9,
+    GLFW_KEY_SEMICOLON <=> Semicolon,
+    GLFW_KEY_EQUAL <=> Equals,
+    GLFW_KEY_A <=> A,
+    GLFW_KEY_B <=> B,
+    GLFW_KEY_C <=> C,
+    GLFW_KEY_D <=> D,
+    GLFW_KEY_E <=> E,
+    GLFW_KEY_F <=> F,
+    GLFW_KEY_G <=> G,
+    GLFW_KEY_H <=> H,
+    GLFW_KEY_I <=> I,
+    GLFW_KEY_J <=> J,
+    GLFW_KEY_K <=> K,
+    GLFW_KEY_L <=> L,
+    GLFW_KEY_M <=> M,
+    GLFW_KEY_N <=> N,
+    GLFW_KEY_O <=> O,
+    GLFW_KEY_P <=> P,
+    GLFW_KEY_Q <=> Q,
+    GLFW_KEY_R <=> R,
+    GLFW_KEY_S <=> S,
+    GLFW_KEY_T <=> T,
+    GLFW_KEY_U <=> U,
+    GLFW_KEY_V <=> V,
+    GLFW_KEY_W <=> W,
+    GLFW_KEY_X <=> X,
+    GLFW_KEY_Y <=> Y,
+    GLFW_KEY_Z <=> Z,
+    GLFW_KEY_LEFT_BRACKET <=> LBracket,
+    GLFW_KEY_BACKSLASH <=> Backslash,
+    GLFW_KEY_RIGHT_BRACKET <=> RBracket,
+    GLFW_KEY_GRAVE_ACCENT <=> Grave,
+    // non-US backslash on X11 and macOS
+    GLFW_KEY_WORLD_1 <=> Oem102,
+    GLFW_KEY_ESCAPE <=> Escape,
+    GLFW_KEY_ENTER <=> Return,
+    GLFW_KEY_TAB <=> Tab,
+    GLFW_KEY_BACKSPACE <=> Back,
+    GLFW_KEY_INSERT <=> Insert,
+    GLFW_KEY_DELETE <=> Delete,
+    GLFW_KEY_RIGHT <=> Right,
+    GLFW_KEY_LEFT <=> Left,
+    GLFW_KEY_DOWN <=> Down,
+    GLFW_KEY_UP <=> Up,
+    GLFW_KEY_PAGE_UP <=> PageUp,
+    GLFW_KEY_PAGE_DOWN <=> PageDown,
+    GLFW_KEY_HOME <=> Home,
+    GLFW_KEY_END <=> End,
+    GLFW_KEY_CAPS_LOCK <=> Capital,
+    GLFW_KEY_SCROLL_LOCK <=> Scroll,
+    GLFW_KEY_NUM_LOCK <=> Numlock,
+    GLFW_KEY_PRINT_SCREEN <=> Snapshot,
+    GLFW_KEY_PAUSE <=> Pause,
+    GLFW_KEY_F1 <=> F1,
+    GLFW_KEY_F2 <=> F2,
+    GLFW_KEY_F3 <=> F3,
+    GLFW_KEY_F4 <=> F4,
+    GLFW_KEY_F5 <=> F5,
+    GLFW_KEY_F6 <=> F6,
+    GLFW_KEY_F7 <=> F7,
+    GLFW_KEY_F8 <=> F8,
+    GLFW_KEY_F9 <=> F9,
+    GLFW_KEY_F10 <=> F10,
+    GLFW_KEY_F11 <=> F11,
+    GLFW_KEY_F12 <=> F12,
+    GLFW_KEY_F13 <=> F13,
+    GLFW_KEY_F14 <=> F14,
+    GLFW_KEY_F15 <=> F15,
+    GLFW_KEY_F16 <=> F16,
+    GLFW_KEY_F17 <=> F17,
+    GLFW_KEY_F18 <=> F18,
+    GLFW_KEY_F19 <=> F19,
+    GLFW_KEY_F20 <=> F20,
+    GLFW_KEY_F21 <=> F21,
+    GLFW_KEY_F22 <=> F22,
+    GLFW_KEY_F23 <=> F23,
+    GLFW_KEY_F24 <=> F24,
+    GLFW_KEY_KP_0 <=> Numpad0,
+    GLFW_KEY_KP_1 <=> Numpad1,
+    GLFW_KEY_KP_2 <=> Numpad2,
+    GLFW_KEY_KP_3 <=> Numpad3,
+    GLFW_KEY_KP_4 <=> Numpad4,
+    GLFW_KEY_KP_5 <=> Numpad5,
+    GLFW_KEY_KP_6 <=> Numpad6,
+    GLFW_KEY_KP_7 <=> Numpad7,
+    GLFW_KEY_KP_8 <=> Numpad8,
+    GLFW_KEY_KP_9 <=> Numpad9,
+    GLFW_KEY_KP_DECIMAL <=> NumpadDecimal,
+    GLFW_KEY_KP_DIVIDE <=> NumpadDivide,
+    GLFW_KEY_KP_MULTIPLY <=> NumpadMultiply,
+    GLFW_KEY_KP_SUBTRACT <=> NumpadSubtract,
+    GLFW_KEY_KP_ADD <=> NumpadAdd,
+    GLFW_KEY_KP_ENTER <=> NumpadEnter,
+    GLFW_KEY_KP_EQUAL <=> NumpadEquals,
+    GLFW_KEY_LEFT_SHIFT <=> LShift,
+    GLFW_KEY_LEFT_CONTROL <=> LControl,
+    GLFW_KEY_LEFT_ALT <=> LAlt,
+    GLFW_KEY_LEFT_SUPER <=> LWin,
+    GLFW_KEY_RIGHT_SHIFT <=> RShift,
+    GLFW_KEY_RIGHT_CONTROL <=> RControl,
+    GLFW_KEY_RIGHT_ALT <=> RAlt,
+    GLFW_KEY_RIGHT_SUPER <=> RWin,
+    GLFW_KEY_MENU <=> Apps,
+}
+
+/// Maps a GLFW mouse button to a [`MouseButton`], buttons past the middle one become
+/// [`MouseButton::Other`] numbered from 4.
+pub fn glfw_mouse_button_to_mouse_button(button: c_int) -> MouseButton {
+    match button + 1 {
+        1 => MouseButton::Left,
+        2 => MouseButton::Right,
+        3 => MouseButton::Middle,
+        other => MouseButton::Other(other as u16),
+    }
+}
+
+/// Maps a [`MouseButton`] to its GLFW mouse button, `None` if GLFW has no such button.
+pub fn mouse_button_to_glfw_mouse_button(button: MouseButton) -> Option<c_int> {
+    match button {
+        MouseButton::Left => Some(GLFW_MOUSE_BUTTON_LEFT as c_int),
+        MouseButton::Right => Some(GLFW_MOUSE_BUTTON_RIGHT as c_int),
+        MouseButton::Middle => Some(GLFW_MOUSE_BUTTON_MIDDLE as c_int),
+        MouseButton::Other(other) => Some(other as c_int - 1)
+            .filter(|&button| (3..=GLFW_MOUSE_BUTTON_LAST as c_int).contains(&button)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::utils::HashSet;
+
+    #[test]
+    fn glfw_keys_round_trip() {
+        for key in GLFW_KEY_SPACE as c_int..=GLFW_KEY_LAST as c_int {
+            if key == GLFW_KEY_WORLD_2 as c_int {
+                continue;
+            }
+
+            if let Some(key_code) = glfw_key_to_keycode(key) {
+                assert_eq!(keycode_to_glfw_key(key_code), Some(key), "{key_code:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn keycodes_round_trip() {
+        use KeyCode::*;
+        let key_codes = [
+            Key1,
+            Key2,
+            Key3,
+            Key4,
+            Key5,
+            Key6,
+            Key7,
+            Key8,
+            Key9,
+            Key0,
+            A,
+            B,
+            C,
+            D,
+            E,
+            F,
+            G,
+            H,
+            I,
+            J,
+            K,
+            L,
+            M,
+            N,
+            O,
+            P,
+            Q,
+            R,
+            S,
+            T,
+            U,
+            V,
+            W,
+            X,
+            Y,
+            Z,
+            Escape,
+            F1,
+            F2,
+            F3,
+            F4,
+            F5,
+            F6,
+            F7,
+            F8,
+            F9,
+            F10,
+            F11,
+            F12,
+            F13,
+            F14,
+            F15,
+            F16,
+            F17,
+            F18,
+            F19,
+            F20,
+            F21,
+            F22,
+            F23,
+            F24,
+            Snapshot,
+            Scroll,
+            Pause,
+            Insert,
+            Home,
+            Delete,
+            End,
+            PageDown,
+            PageUp,
+            Left,
+            Up,
+            Right,
+            Down,
+            Back,
+            Return,
+            Space,
+            Compose,
+            Caret,
+            Numlock,
+            Numpad0,
+            Numpad1,
+            Numpad2,
+            Numpad3,
+            Numpad4,
+            Numpad5,
+            Numpad6,
+            Numpad7,
+            Numpad8,
+            Numpad9,
+            AbntC1,
+            AbntC2,
+            NumpadAdd,
+            Apostrophe,
+            Apps,
+            Asterisk,
+            Plus,
+            At,
+            Ax,
+            Backslash,
+            Calculator,
+            Capital,
+            Colon,
+            Comma,
+            Convert,
+            NumpadDecimal,
+            NumpadDivide,
+            Equals,
+            Grave,
+            Kana,
+            Kanji,
+            LAlt,
+            LBracket,
+            LControl,
+            LShift,
+            LWin,
+            Mail,
+            MediaSelect,
+            MediaStop,
+            Minus,
+            NumpadMultiply,
+            Mute,
+            MyComputer,
+            NavigateForward,
+            NavigateBackward,
+            NextTrack,
+            NoConvert,
+            NumpadComma,
+            NumpadEnter,
+            NumpadEquals,
+            Oem102,
+            Period,
+            PlayPause,
+            Power,
+            PrevTrack,
+            RAlt,
+            RBracket,
+            RControl,
+            RShift,
+            RWin,
+            Semicolon,
+            Slash,
+            Sleep,
+            Stop,
+            NumpadSubtract,
+            Sysrq,
+            Tab,
+            Underline,
+            Unlabeled,
+            VolumeDown,
+            VolumeUp,
+            Wake,
+            WebBack,
+            WebFavorites,
+            WebForward,
+            WebHome,
+            WebRefresh,
+            WebSearch,
+            WebStop,
+            Yen,
+            Copy,
+            Paste,
+            Cut,
+        ];
+
+        let mut mapped = 0;
+        for key_code in key_codes {
+            if let Some(key) = keycode_to_glfw_key(key_code) {
+                assert_eq!(glfw_key_to_keycode(key), Some(key_code), "{key_code:?}");
+                mapped += 1;
+            }
+        }
+
+        // GLFW_KEY_F25 has no KeyCode, GLFW_KEY_WORLD_2 shares KeyCode::Oem102 with GLFW_KEY_WORLD_1
+        let glfw_key_codes: HashSet<_> = (GLFW_KEY_SPACE as c_int..=GLFW_KEY_LAST as c_int)
+            .filter_map(glfw_key_to_keycode)
+            .collect();
+        assert_eq!(mapped, glfw_key_codes.len());
+    }
+
+    #[test]
+    fn mouse_buttons_round_trip() {
+        for button in GLFW_MOUSE_BUTTON_1 as c_int..=GLFW_MOUSE_BUTTON_LAST as c_int {
+            let mouse_button = glfw_mouse_button_to_mouse_button(button);
+            assert_eq!(
+                mouse_button_to_glfw_mouse_button(mouse_button),
+                Some(button),
+                "{mouse_button:?}"
+            );
+        }
+
+        for other in [0, 1, 2, 3, 9, u16::MAX] {
+            assert_eq!(
+                mouse_button_to_glfw_mouse_button(MouseButton::Other(other)),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn special_keys() {
+        let keys = [
+            (GLFW_KEY_CAPS_LOCK, Some(KeyCode::Capital)),
+            (GLFW_KEY_MENU, Some(KeyCode::Apps)),
+            (GLFW_KEY_WORLD_1, Some(KeyCode::Oem102)),
+            (GLFW_KEY_WORLD_2, Some(KeyCode::Oem102)),
+            (GLFW_KEY_F25, None),
+        ];
+
+        for (key, key_code) in keys {
+            assert_eq!(glfw_key_to_keycode(key as c_int), key_code);
+        }
+
+        assert_eq!(glfw_key_to_keycode(GLFW_KEY_UNKNOWN), None);
+    }
+}
@@ -1,26 +1,58 @@
 #![doc = include_str!("../README.md")]
+mod glfw_app_runner;
+mod glfw_errors;
+mod glfw_frame_limiter;
+mod glfw_gamepads;
+mod glfw_input;
+mod glfw_joysticks;
+mod glfw_monitors;
+mod glfw_proxy;
+#[cfg(feature = "render")]
+mod glfw_render;
+mod glfw_settings;
 mod glfw_windows;
+pub mod keys;
+
+pub use glfw_app_runner::GlfwAppRunner;
+pub use glfw_errors::{GlfwError, GlfwErrorEvent};
+pub use glfw_frame_limiter::GlfwFrameLimiterDiagnosticsPlugin;
+#[cfg(feature = "asset")]
+pub use glfw_gamepads::GlfwGamepadMappingsAsset;
+pub use glfw_gamepads::{GlfwGamepadMappingError, GlfwGamepadMappings, GlfwGamepadPlugin};
+pub use glfw_input::{
+    GlfwKeyNames, GlfwKeyboardInput, GlfwKeyboardLayoutChanged, GlfwLockKeysChanged, GlfwModifiers,
+    GlfwModifiersChanged,
+};
+pub use glfw_joysticks::{
+    GlfwJoystick, GlfwJoystickConnection, GlfwJoystickConnectionEvent, GlfwJoystickEvent,
+    GlfwJoystickEventType, GlfwJoystickPlugin, GlfwJoysticks,
+};
+pub use glfw_monitors::{GlfwMonitor, GlfwMonitorPlugin, GlfwMonitors, GlfwVideoMode};
+pub use glfw_proxy::GlfwEventLoopProxy;
+pub use glfw_settings::{
+    GlfwAnglePlatformType, GlfwPlatform, GlfwSettings, GlfwUpdateMode, PrimaryWindowFallback,
+};
+pub use glfw_windows::{
+    GlfwWindowStates, WindowCreationError, WindowCreationFailed, WindowMaximized, WindowMinimized,
+};
 
 use bevy::{
-    app::AppExit,
-    ecs::event::ManualEventReader,
+    math::DVec2,
     prelude::*,
     window::{
         CreateWindow, ModifiesWindows, WindowClosed, WindowCommand, WindowCreated, WindowMode,
     },
 };
 use glfw_bindgen::*;
+use glfw_errors::{glfw_error_callback, SilentErrorScope, WindowErrorScope};
+use glfw_input::detect_keyboard_layout_change;
+use glfw_joysticks::glfw_joystick_callback;
 use glfw_windows::GlfwWindows;
 use std::{
-    ffi::{c_char, c_int, CStr, CString},
-    ptr,
+    ffi::{c_int, CString},
+    process, ptr,
 };
 
-unsafe extern "C" fn glfw_error_callback(error_code: c_int, description: *const c_char) {
-    let description = CStr::from_ptr(description).to_string_lossy();
-    debug!("GLFW error {error_code}: {description}");
-}
-
 #[derive(Default)]
 pub struct GlfwPlugin;
 
@@ -31,16 +63,62 @@ impl Plugin for GlfwPlugin {
             "GlfwPlugin added multiple times"
         );
 
+        let settings = app
+            .world
+            .get_resource_or_insert_with(GlfwSettings::default)
+            .clone();
+
         unsafe {
             glfwSetErrorCallback(Some(glfw_error_callback));
+            settings.apply_init_hints();
             assert_eq!(glfwInit(), GLFW_TRUE as c_int);
+            glfwSetJoystickCallback(Some(glfw_joystick_callback));
+            glfw_joysticks::connect_present_joysticks();
         }
 
         app.init_non_send_resource::<GlfwWindows>()
+            .init_resource::<GlfwEventLoopProxy>()
+            .init_resource::<GlfwModifiers>()
+            .add_event::<GlfwErrorEvent>()
+            .add_event::<WindowCreationFailed>()
+            .add_event::<WindowMinimized>()
+            .add_event::<WindowMaximized>()
+            .add_event::<GlfwModifiersChanged>()
+            .add_event::<GlfwLockKeysChanged>()
+            .add_event::<GlfwKeyboardInput>()
+            .add_event::<GlfwKeyboardLayoutChanged>()
+            .add_event::<GlfwJoystickConnectionEvent>()
             .set_runner(glfw_runner)
+            .add_system_to_stage(CoreStage::PreUpdate, detect_keyboard_layout_change)
             .add_system_to_stage(CoreStage::PostUpdate, change_window.label(ModifiesWindows));
 
-        handle_create_window_events(&mut app.world);
+        #[cfg(feature = "render")]
+        {
+            let released_surfaces = glfw_render::setup_surface_release(app);
+            // null platform windows have no native handle to create a surface for
+            if released_surfaces.is_some() && settings.platform == GlfwPlatform::Null {
+                panic!(
+                    "GlfwSettings::headless() doesn't support rendering, \
+                    use MinimalPlugins instead of DefaultPlugins or remove RenderPlugin"
+                );
+            }
+
+            // closed windows can't wait for their surface to be released
+            if released_surfaces.is_none() && settings.platform != GlfwPlatform::Null {
+                warn!(
+                    "No RenderApp found, add GlfwPlugin after RenderPlugin or disable the `render` \
+                    feature, closing windows while rendering may deadlock otherwise"
+                );
+            }
+
+            app.world
+                .non_send_resource_mut::<GlfwWindows>()
+                .released_surfaces = released_surfaces;
+        }
+
+        handle_create_window_events(&mut app.world, Vec::new());
+        glfw_errors::send_error_events(&mut app.world);
+        glfw_joysticks::send_joystick_events(&mut app.world);
     }
 }
 
@@ -49,19 +127,21 @@ fn change_window(
     mut windows: ResMut<Windows>,
     mut window_close_events: EventWriter<WindowClosed>,
 ) {
+    let glfw_windows = &mut *glfw_windows;
     let mut removed_windows = vec![];
     for bevy_window in windows.iter_mut() {
         let id = bevy_window.id();
-        let cursors = glfw_windows.cursors.clone();
+        let cursors = &glfw_windows.cursors;
         let window = glfw_windows.windows.get_mut(&id).unwrap();
+        let _error_scope = WindowErrorScope::new(id);
         for command in bevy_window.drain_commands() {
             match command {
                 WindowCommand::SetWindowMode { mode, resolution } => unsafe {
                     window.set_window_mode(mode, resolution);
                 },
-                WindowCommand::SetTitle { title } => unsafe {
-                    let title = CString::new(title.as_str()).expect("Invalid window title");
-                    glfwSetWindowTitle(window.window, title.as_ptr());
+                WindowCommand::SetTitle { title } => match CString::new(title) {
+                    Ok(title) => unsafe { glfwSetWindowTitle(window.window, title.as_ptr()) },
+                    Err(err) => error!("Invalid title for window {id}: {err}"),
                 },
                 WindowCommand::SetScaleFactor { .. } => {
                     panic!("Manually changing window scale factor not supported");
@@ -114,11 +194,11 @@ fn change_window(
                     unsafe { window.update_cursor_mode() };
                 }
                 WindowCommand::SetCursorPosition { position } => unsafe {
-                    glfwSetCursorPos(
-                        window.window,
-                        position.x as f64,
-                        window.size.y as f64 - position.y as f64,
-                    );
+                    let position =
+                        DVec2::new(position.x as f64, window.size.y as f64 - position.y as f64);
+                    // moving the cursor isn't mouse motion
+                    window.last_cursor_pos = Some(position);
+                    glfwSetCursorPos(window.window, position.x, position.y);
                 },
                 WindowCommand::SetMaximized { maximized } => unsafe {
                     if maximized {
@@ -154,61 +234,107 @@ fn change_window(
     for id in removed_windows {
         if windows.remove(id).is_some() {
             window_close_events.send(WindowClosed { id });
-            let mut window = glfw_windows.windows.remove(&id).unwrap();
-            if !glfw_windows.windows.is_empty() {
-                unsafe {
-                    // glfwHideWindow only works on windowed windows
-                    window.set_window_mode(WindowMode::Windowed, UVec2::default());
-                    // HACK: glfwDestroyWindow may cause vkDestroySwapchain to deadlock
-                    glfwHideWindow(window.window);
-                }
-            }
+            let _error_scope = WindowErrorScope::new(id);
+            unsafe { glfw_windows.close_window(id) };
         }
     }
 }
 
-pub fn glfw_runner(mut app: App) {
-    let mut app_exit_event_reader = ManualEventReader::<AppExit>::default();
-    loop {
-        {
-            unsafe { glfwPollEvents() };
-            let world = app.world.cell();
-            let mut glfw_windows = world.non_send_resource_mut::<GlfwWindows>();
-            for (window_id, window) in glfw_windows.windows.iter_mut() {
-                let mut windows = world.resource_mut::<Windows>();
-                let bevy_window = windows.get_mut(*window_id).unwrap();
-                unsafe { window.handle_events(&world, bevy_window) };
-            }
-        }
-
-        app.update();
-
-        if let Some(app_exit_events) = app.world.get_resource::<Events<AppExit>>() {
-            if app_exit_event_reader.iter(app_exit_events).last().is_some() {
-                break;
-            }
-        }
+pub fn glfw_runner(app: App) {
+    drop(glfw_run_return(app));
+    unsafe { glfwTerminate() };
+}
 
-        handle_create_window_events(&mut app.world);
+/// Like [`glfw_runner`], but returns the app instead of dropping it.
+///
+/// GLFW stays initialized, call [`terminate`] after dropping the app.
+pub fn glfw_run_return(app: App) -> App {
+    let mut runner = GlfwAppRunner::new(app);
+    while !runner.should_exit() {
+        runner.poll_events();
+        runner.update();
     }
 
-    drop(app);
-    unsafe { glfwTerminate() };
+    runner.into_app()
 }
 
-fn handle_create_window_events(world: &mut World) {
+/// Terminates GLFW, which [`glfw_run_return`] and [`GlfwAppRunner`] leave initialized.
+///
+/// # Safety
+///
+/// No app using [`GlfwPlugin`] may be alive.
+pub unsafe fn terminate() {
+    glfwTerminate();
+}
+
+/// Creates the windows of `pending_events`, followed by the ones of the current [`CreateWindow`] events.
+fn handle_create_window_events(world: &mut World, mut pending_events: Vec<CreateWindow>) {
+    pending_events.extend(
+        world
+            .get_resource_mut::<Events<CreateWindow>>()
+            .unwrap()
+            .drain(),
+    );
+
     let world = world.cell();
-    for create_window_event in world
-        .get_resource_mut::<Events<CreateWindow>>()
-        .unwrap()
-        .drain()
-    {
+    let settings = world.resource::<GlfwSettings>();
+    for create_window_event in pending_events {
         let mut windows = world.get_resource_mut::<Windows>().unwrap();
         let mut window_created_events = world.get_resource_mut::<Events<WindowCreated>>().unwrap();
-        let window = world
-            .non_send_resource_mut::<GlfwWindows>()
-            .create_window(create_window_event.id, &create_window_event.descriptor);
-        window_created_events.send(WindowCreated { id: window.id() });
-        windows.add(window);
+        let mut window_creation_failed_events =
+            world.resource_mut::<Events<WindowCreationFailed>>();
+        let mut glfw_windows = world.non_send_resource_mut::<GlfwWindows>();
+
+        let CreateWindow { id, descriptor } = create_window_event;
+        let retry = id.is_primary()
+            && matches!(
+                settings.primary_window_fallback,
+                PrimaryWindowFallback::RetryWithFewerHints
+            );
+
+        let mut result = {
+            // only reported if the retry fails too
+            let _silent_error_scope = retry.then(SilentErrorScope::new);
+            glfw_windows.create_window(id, &descriptor, &settings)
+        };
+
+        if let (Err(reason), true) = (&result, retry) {
+            warn!("Failed to create primary window, retrying with fewer hints: {reason}");
+            let descriptor = WindowDescriptor {
+                scale_factor_override: None,
+                resizable: true,
+                decorations: true,
+                mode: WindowMode::Windowed,
+                transparent: false,
+                ..descriptor
+            };
+
+            result = glfw_windows.create_window(id, &descriptor, &settings);
+        }
+
+        if let Err(reason) = &result {
+            error!("Failed to create window {id}: {reason}");
+            window_creation_failed_events.send(WindowCreationFailed {
+                id,
+                reason: reason.clone(),
+            });
+
+            if id.is_primary() {
+                match settings.primary_window_fallback {
+                    PrimaryWindowFallback::Panic | PrimaryWindowFallback::RetryWithFewerHints => {
+                        panic!("Failed to create primary window: {reason}")
+                    }
+                    PrimaryWindowFallback::Exit => unsafe {
+                        glfwTerminate();
+                        process::exit(1);
+                    },
+                }
+            }
+        }
+
+        if let Ok(window) = result {
+            window_created_events.send(WindowCreated { id: window.id() });
+            windows.add(window);
+        }
     }
 }
@@ -0,0 +1,45 @@
+use bevy::{ecs::system::SystemState, input::InputPlugin, prelude::*, window::WindowPlugin};
+use bevy_glfw::{
+    GlfwGamepadMappingError, GlfwGamepadMappings, GlfwGamepadPlugin, GlfwPlugin, GlfwSettings,
+};
+
+const GUID: &str = "030000005e0400008e02000014010000";
+
+#[test]
+fn gamepad_mappings() {
+    let mut app = App::new();
+    app.insert_resource(GlfwSettings::headless())
+        .add_plugins(MinimalPlugins)
+        .add_plugin(WindowPlugin)
+        .add_plugin(InputPlugin)
+        .add_plugin(GlfwPlugin)
+        .add_plugin(GlfwGamepadPlugin);
+
+    let lines = [
+        // comments and blank lines are ignored
+        "# Test mappings".to_string(),
+        String::new(),
+        format!("{GUID},Valid,a:b0,b:b1,leftx:a0,lefty:a1,lefttrigger:+a2,"),
+        format!("{GUID},Other Platform,a:b0,platform:Windows,"),
+        "0123,Malformed GUID,a:b0,".to_string(),
+        // the null platform accepts mappings for every platform
+        format!("{GUID},Output Modifier,a:b0,+leftx:a0,platform:Windows,"),
+        format!("{GUID},Too Long{}", ",a:b0".repeat(250)),
+        format!("{GUID},Nul\0,a:b0,"),
+    ];
+
+    let mut state = SystemState::<GlfwGamepadMappings>::new(&mut app.world);
+    state.get_mut(&mut app.world).update(&lines.join("\n"));
+    state.apply(&mut app.world);
+
+    let events = app.world.resource::<Events<GlfwGamepadMappingError>>();
+    let errors: Vec<_> = events.get_reader().iter(events).cloned().collect();
+    let line_numbers: Vec<_> = errors.iter().map(|error| error.line_number).collect();
+    assert_eq!(line_numbers, [5, 6, 7, 8]);
+    assert!(errors
+        .iter()
+        .all(|error| error.line == lines[error.line_number - 1]));
+
+    drop(app);
+    unsafe { bevy_glfw::terminate() };
+}
@@ -0,0 +1,67 @@
+use bevy::{
+    input::InputPlugin,
+    prelude::*,
+    window::{CreateWindow, WindowClosed, WindowCreated, WindowId, WindowPlugin},
+};
+use bevy_glfw::{GlfwAppRunner, GlfwPlugin, GlfwSettings};
+
+fn step(runner: &mut GlfwAppRunner, frames: usize) {
+    for _ in 0..frames {
+        runner.poll_events();
+        runner.update();
+    }
+}
+
+fn sent_events<E: Clone + Send + Sync + 'static>(runner: &GlfwAppRunner) -> Vec<E> {
+    let events = runner.app().world.resource::<Events<E>>();
+    events.get_reader().iter(events).cloned().collect()
+}
+
+#[test]
+fn headless() {
+    let mut app = App::new();
+    app.insert_resource(GlfwSettings::headless())
+        .add_plugins(MinimalPlugins)
+        .add_plugin(WindowPlugin)
+        .add_plugin(InputPlugin)
+        .add_plugin(GlfwPlugin);
+
+    let mut runner = GlfwAppRunner::new(app);
+    step(&mut runner, 2);
+    let windows = runner.app().world.resource::<Windows>();
+    assert!(windows.get_primary().is_some());
+
+    let id = WindowId::new();
+    runner.app_mut().world.send_event(CreateWindow {
+        id,
+        descriptor: WindowDescriptor {
+            width: 200.0,
+            height: 100.0,
+            title: "Secondary".to_string(),
+            ..default()
+        },
+    });
+
+    step(&mut runner, 1);
+    let created = sent_events::<WindowCreated>(&runner);
+    assert!(created.iter().any(|event| event.id == id));
+
+    let mut windows = runner.app_mut().world.resource_mut::<Windows>();
+    let window = windows.get_mut(id).unwrap();
+    window.set_title("Changed".to_string());
+    window.set_resolution(300.0, 200.0);
+    step(&mut runner, 2);
+    let window = runner.app().world.resource::<Windows>().get(id).unwrap();
+    assert_eq!((window.width(), window.height()), (300.0, 200.0));
+
+    let mut windows = runner.app_mut().world.resource_mut::<Windows>();
+    windows.get_mut(id).unwrap().close();
+    step(&mut runner, 2);
+    assert!(runner.app().world.resource::<Windows>().get(id).is_none());
+    let closed = sent_events::<WindowClosed>(&runner);
+    assert!(closed.iter().any(|event| event.id == id));
+    assert!(!runner.should_exit());
+
+    drop(runner.into_app());
+    unsafe { bevy_glfw::terminate() };
+}